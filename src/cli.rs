@@ -0,0 +1,223 @@
+use std::process::ExitCode;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use crate::display_tools::tui::app::run_app;
+use crate::modem_tools::converters::DEFAULT_GNB_ID_BITS;
+use crate::modem_tools::export::{BandsReport, ExportFormat, SignalReport};
+use crate::modem_tools::modem::{modem_execute, poll_modem};
+use crate::modem_tools::session::ModemSession;
+use crate::modem_tools::supported_modems::{detect_modem, BandCapabilities, Modem, ModemProfile};
+use crate::modem_tools::types::ModemInfo;
+use crate::serial_tools::transport::Endpoint;
+
+/// The modem answered with ERROR, +CME ERROR or +CMS ERROR
+const EXIT_MODEM_ERROR: u8 = 1;
+/// Bad command line, same code clap uses
+const EXIT_USAGE: u8 = 2;
+/// The port could not be opened or the modem did not answer
+const EXIT_CONNECTION: u8 = 3;
+
+#[derive(Parser)]
+#[command(version, about = "Monitor and configure Fibocom/Intel LTE modems")]
+pub struct Cli {
+    /// Modem AT port: /dev/ttyACM0, COM3, tcp://host:port, unix:///path, sim://[scenario] or replay://file
+    #[arg(short, long, global = true)]
+    port: Option<String>,
+
+    #[arg(short, long, default_value_t = 115_200, global = true)]
+    baud_rate: u32,
+
+    /// Record all modem traffic to this session file
+    #[arg(long, global = true)]
+    record: Option<String>,
+
+    /// Append every poll result of the interactive interface to this CSV or JSONL (.jsonl) file
+    #[arg(long)]
+    history: Option<String>,
+
+    /// Length of the gNB ID in NR cell identities, depends on the operator
+    #[arg(long, default_value_t = DEFAULT_GNB_ID_BITS, value_parser = clap::value_parser!(u32).range(22..=32), global = true)]
+    gnb_id_bits: u32,
+
+    /// Output format of info, signal and bands get
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Text, global = true)]
+    format: ExportFormat,
+
+    /// Without a command the interactive interface is started
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Modem identity, network and signal report
+    Info,
+    /// Signal quality of the serving and neighbour cells
+    Signal,
+    /// Show or change the enabled bands
+    Bands {
+        #[command(subcommand)]
+        action: BandsAction,
+    },
+    /// Send a raw AT command and print the response
+    At {
+        command: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BandsAction {
+    /// Print the enabled UMTS, LTE and NR bands
+    Get,
+    /// Enable only the given bands, e.g. `bands set --lte 3,7,20 --nr 78`
+    Set {
+        #[arg(long, value_delimiter = ',')]
+        umts: Vec<usize>,
+        #[arg(long, value_delimiter = ',')]
+        lte: Vec<usize>,
+        #[arg(long, value_delimiter = ',')]
+        nr: Vec<usize>,
+        /// Enable every band the modem supports again
+        #[arg(long, conflicts_with_all = ["umts", "lte", "nr"])]
+        all: bool,
+    },
+}
+
+pub fn run(cli: Cli) -> ExitCode {
+    let Some(command) = cli.command else {
+        return match run_app(cli.port, cli.baud_rate, cli.record, cli.history, cli.gnb_id_bits) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => fail(EXIT_CONNECTION, &err.to_string()),
+        };
+    };
+
+    let Some(port_name) = cli.port else {
+        return fail(EXIT_USAGE, "--port is required for non-interactive commands");
+    };
+    let session = match ModemSession::open(&Endpoint::parse(&port_name), cli.baud_rate) {
+        Ok(session) => session,
+        Err(err) => return fail(EXIT_CONNECTION, &format!("cannot open {}: {}", port_name, err)),
+    };
+    if let Some(path) = &cli.record {
+        if let Err(err) = session.record_to(path) {
+            return fail(EXIT_CONNECTION, &format!("cannot record session to {}: {}", path, err));
+        }
+    }
+
+    let format = cli.format;
+    let gnb_id_bits = cli.gnb_id_bits;
+    match command {
+        Command::Info => with_modem_info(&session, gnb_id_bits, |_, _, info| {
+            report(format, info, || {
+                format!("{}\n{}\n{}", info.display_modem_info(), info.display_signal_info(), info.display_carrier_info())
+            })
+        }),
+        Command::Signal => with_modem_info(&session, gnb_id_bits, |_, _, info| {
+            report(format, &SignalReport::from(info), || {
+                format!("{}\n{}", info.display_signal_info(), info.display_carrier_info())
+            })
+        }),
+        Command::Bands { action: BandsAction::Get } => with_modem_info(&session, gnb_id_bits, |_, _, info| {
+            let bands = BandsReport { umts: &info.enabled_umts_bands, lte: &info.enabled_lte_bands, nr: &info.enabled_nr_bands };
+            report(format, &bands, || {
+                let mut text = format!("UMTS: {}\nLTE:  {}\n", join_bands(bands.umts), join_bands(bands.lte));
+                if !bands.nr.is_empty() {
+                    text.push_str(&format!("NR:   {}\n", join_bands(bands.nr)));
+                }
+                text
+            })
+        }),
+        Command::Bands { action: BandsAction::Set { umts, lte, nr, all } } => {
+            if umts.is_empty() && lte.is_empty() && nr.is_empty() && !all {
+                return fail(EXIT_USAGE, "no bands given, use --all to enable every band");
+            }
+            // Locking needs only the profile and its bands, not a full poll
+            with_modem(&session, |modem| {
+                let (profile, supported) = (modem.profile, &modem.bands);
+                let Some(command) = profile.band_lock_command(&umts, &lte, &nr) else {
+                    return fail(EXIT_USAGE, &format!("{} does not support band selection", profile.name()));
+                };
+                let unsupported: Vec<String> = [
+                    ("UMTS", &umts, &supported.umts),
+                    ("LTE", &lte, &supported.lte),
+                    ("NR", &nr, &supported.nr),
+                ]
+                    .into_iter()
+                    .filter_map(|(rat, bands, supported)| {
+                        let unsupported: Vec<usize> = bands.iter().filter(|b| !supported.contains(b)).copied().collect();
+                        (!unsupported.is_empty()).then(|| format!("{} {}", rat, join_bands(&unsupported)))
+                    })
+                    .collect();
+                if !unsupported.is_empty() {
+                    return fail(EXIT_USAGE, &format!("bands not supported by {}: {}", profile.name(), unsupported.join("; ")));
+                }
+                execute(&session, &command)
+            })
+        }
+        Command::At { command } => execute(&session, &command),
+    }
+}
+
+fn with_modem(session: &ModemSession, report: impl FnOnce(&Modem) -> ExitCode) -> ExitCode {
+    let modem = match detect_modem(session) {
+        Ok(modem) => modem,
+        Err(err) => return fail(EXIT_CONNECTION, &err.to_string()),
+    };
+    if modem.generic {
+        eprintln!("modem-manager: unsupported modem {}, generic 3GPP mode", modem.identity.describe());
+    }
+    report(&modem)
+}
+
+fn with_modem_info(session: &ModemSession, gnb_id_bits: u32, report: impl FnOnce(&dyn ModemProfile, &BandCapabilities, &ModemInfo) -> ExitCode) -> ExitCode {
+    with_modem(session, |modem| {
+        let profile = modem.profile;
+        let poll = poll_modem(session, profile);
+        if poll.is_empty() {
+            let issues: Vec<String> = poll.unanswered.issues.iter().map(|issue| issue.to_string()).collect();
+            return fail(EXIT_CONNECTION, &issues.join("; "));
+        }
+        let mut info = poll.parse(profile);
+        info.decode_nr_cell_ids(gnb_id_bits);
+        if !info.parse_report.is_ok() {
+            // The rest of the poll is still reported
+            for issue in &info.parse_report.issues {
+                eprintln!("modem-manager: {}", issue);
+            }
+        }
+        report(profile, &modem.bands, &info)
+    })
+}
+
+/// Prints `value` in the requested format, `text` renders the human-readable one.
+fn report<T: Serialize>(format: ExportFormat, value: &T, text: impl FnOnce() -> String) -> ExitCode {
+    match format.serialize(value) {
+        None => print!("{}", text()),
+        Some(Ok(serialized)) => println!("{}", serialized.trim_end()),
+        Some(Err(err)) => return fail(EXIT_MODEM_ERROR, &format!("cannot serialize report: {}", err)),
+    }
+    ExitCode::SUCCESS
+}
+
+fn execute(session: &ModemSession, command: &str) -> ExitCode {
+    match modem_execute(session, command) {
+        Ok(response) => {
+            println!("{}", response);
+            if response.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(EXIT_MODEM_ERROR)
+            }
+        }
+        Err(err) => fail(EXIT_CONNECTION, &err.to_string()),
+    }
+}
+
+fn join_bands(bands: &[usize]) -> String {
+    bands.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(",")
+}
+
+fn fail(code: u8, message: &str) -> ExitCode {
+    eprintln!("modem-manager: {}", message);
+    ExitCode::from(code)
+}
@@ -1,7 +1,6 @@
 use std::io::{Stdout, stdout};
-use std::sync::{mpsc};
+use std::sync::{mpsc, Mutex};
 use std::{io, thread};
-use std::process::exit;
 use std::time::{Duration, Instant};
 use crossterm::event::poll;
 use ratatui::{
@@ -19,18 +18,26 @@ use ratatui::{
     Frame
 };
 use ratatui::widgets::Tabs;
-use crate::modem_tools::modem::{get_modem_info, get_modem_info_string};
+use crate::modem_tools::history::HistoryWriter;
+use crate::modem_tools::modem::{poll_modem, ModemPoll};
+use crate::modem_tools::session::ModemSession;
+use crate::serial_tools::transport::Endpoint;
 use crate::display_tools::tui::port_choice_menu::show_port_choice_menu;
 use crate::display_tools::tui::app_tabs::{AppTabs, BandsSelectorActive, SelectedTab};
 use strum::IntoEnumIterator;
 use crate::display_tools::tui::errors;
-use crate::modem_tools::supported_modems::Modem;
+use crate::display_tools::tui::unsupported_modem::show_unsupported_modem_screen;
+use crate::modem_tools::supported_modems::detect_modem;
 
 #[derive(Default, Clone)]
 pub struct App {
     pub(crate) app_tabs: AppTabs,
     pub(crate) port_name: String,
     pub(crate) baud_rate: u32,
+    pub(crate) session: Option<ModemSession>,
+    pub(crate) record_path: Option<String>,
+    pub(crate) history_path: Option<String>,
+    pub(crate) gnb_id_bits: u32,
     pub(crate) exit: bool
 }
 
@@ -39,16 +46,36 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 impl App {
     pub fn run(&mut self, terminal: &mut Tui) -> io::Result<()> {
-        self.baud_rate = 115_200;
-        self.port_name = show_port_choice_menu(terminal)?;
-        if self.port_name == "" {
-            self.exit = true
+        if self.port_name.is_empty() {
+            self.port_name = show_port_choice_menu(terminal)?;
+        }
+        if self.port_name.is_empty() {
+            self.exit = true;
+            return Ok(());
         }
 
-        let port_name = self.port_name.clone();
-        let baud_rate = self.baud_rate.clone();
+        // Returned errors are printed by `run_app` once the terminal is restored
+        let session = ModemSession::open(&Endpoint::parse(&self.port_name), self.baud_rate)
+            .map_err(|err| io::Error::new(err.kind(), format!("cannot open {}: {}", self.port_name, err)))?;
+        if let Some(path) = &self.record_path {
+            session.record_to(path)
+                .map_err(|err| io::Error::new(err.kind(), format!("cannot record session to {}: {}", path, err)))?;
+        }
+        if let Some(path) = &self.history_path {
+            let writer = HistoryWriter::create(path)
+                .map_err(|err| io::Error::new(err.kind(), format!("cannot write signal history to {}: {}", path, err)))?;
+            self.app_tabs.history.lock().unwrap().stream_to(writer);
+        }
+        let modem = detect_modem(&session).map_err(io::Error::other)?;
+        if modem.generic && !show_unsupported_modem_screen(terminal, &modem.identity)? {
+            self.exit = true;
+            return Ok(());
+        }
+        let profile = modem.profile;
+        self.app_tabs.modem_capabilities = modem;
+        self.session = Some(session.clone());
 
-        self.app_tabs.active_bands_selector = BandsSelectorActive::UMTSBandsSelector;
+        self.app_tabs.active_bands_selector = BandsSelectorActive::Umts;
         self.app_tabs.umts_bands_list_state.select(Some(0));
         self.app_tabs.lte_bands_list_state.select(None);
 
@@ -56,15 +83,20 @@ impl App {
 
         let poll_interval = Duration::from_secs(3);
         let mut last_poll_time = Instant::now();
+        let mut refresh_now = false;
 
         self.update_modem_info();
         let modem_info = self.app_tabs.modem_info.clone();
+        let last_event = self.app_tabs.last_event.clone();
+        let history = self.app_tabs.history.clone();
+        let gnb_id_bits = self.gnb_id_bits;
+        let urcs = session.subscribe();
 
         {
             let mi = modem_info.lock().unwrap();
-            self.app_tabs.modem_capabilities.spec = Some(Modem::new(mi.model.as_str()).unwrap());
             self.app_tabs.config_umts_bands = mi.enabled_umts_bands.clone();
             self.app_tabs.config_lte_bands = mi.enabled_lte_bands.clone();
+            self.app_tabs.config_nr_bands = mi.enabled_nr_bands.clone();
         }
         let _update_handle = thread::spawn(move || {
             loop {
@@ -72,25 +104,32 @@ impl App {
                     break;
                 }
 
-                if last_poll_time.elapsed() >= poll_interval {
-                    let modem_info_string = get_modem_info_string(port_name.as_str(), baud_rate).unwrap_or_else(|err| {
-                        eprintln!("{}", err);
-                        String::new()
-                    });
-
-                    if modem_info_string.is_empty() {
-                        thread::sleep(Duration::from_secs(5));
-                        break
-                    }
-                    let updated_info = get_modem_info(modem_info_string).unwrap();
-                    {
-                        let mut info = modem_info.lock().unwrap();
-                        *info = updated_info;
+                if last_poll_time.elapsed() >= poll_interval || refresh_now {
+                    let poll = poll_modem(&session, profile);
+                    report_unanswered(&poll, &last_event);
+                    // Nothing answered: keep showing the last poll until the modem is back
+                    if !poll.is_empty() {
+                        let mut updated_info = poll.parse(profile);
+                        updated_info.decode_nr_cell_ids(gnb_id_bits);
+                        if let Err(err) = history.lock().unwrap().record(&updated_info) {
+                            *last_event.lock().unwrap() = format!("signal history not written: {}", err);
+                        }
+                        {
+                            let mut info = modem_info.lock().unwrap();
+                            *info = updated_info;
+                        }
                     }
                     last_poll_time = Instant::now();
+                    refresh_now = false;
                 }
 
-                thread::sleep(Duration::from_millis(100));
+                // Registration, SMS and CA changes are shown right away instead of on the next poll
+                if let Ok(urc) = urcs.recv_timeout(Duration::from_millis(100)) {
+                    // A registration URC carries the new state itself
+                    modem_info.lock().unwrap().registration.update(&urc);
+                    refresh_now |= urc.changes_modem_state();
+                    *last_event.lock().unwrap() = urc.line;
+                }
             }
         });
 
@@ -104,12 +143,19 @@ impl App {
     }
 
     fn update_modem_info(&self) {
-        let modem_info_string = get_modem_info_string(self.port_name.as_str(), self.baud_rate).unwrap_or_else(|err| {
-                        eprintln!("{}", err);
-                        exit(1);
-                    });
+        let session = self.session.as_ref().expect("modem session is not opened");
+        let profile = self.app_tabs.modem_capabilities.profile;
+        let poll = poll_modem(session, profile);
+        report_unanswered(&poll, &self.app_tabs.last_event);
+        if poll.is_empty() {
+            return;
+        }
 
-        let updated_info = get_modem_info(modem_info_string).unwrap();
+        let mut updated_info = poll.parse(profile);
+        updated_info.decode_nr_cell_ids(self.gnb_id_bits);
+        if let Err(err) = self.app_tabs.history.lock().unwrap().record(&updated_info) {
+            *self.app_tabs.last_event.lock().unwrap() = format!("signal history not written: {}", err);
+        }
 
         {
             let modem_info = self.app_tabs.modem_info.clone();
@@ -216,13 +262,20 @@ impl Widget for &App {
         self.app_tabs.clone().render(inner_area, buf);
 
 
-        "Fibocom L8[5,6]0-GL".bold().render(title_area, buf);
-        Line::raw("◄ ► or Tab to change tab| F10 to save | q or Esc to quit without saving")
+        self.app_tabs.modem_capabilities.profile.name().bold().render(title_area, buf);
+        Line::raw("◄ ► or Tab to change tab| F10 to save | F9 to export history | q or Esc to quit without saving")
         .centered()
         .render(footer_area, buf);
     }
 }
 
+/// Shows the commands the last poll got no answer to in the event line
+fn report_unanswered(poll: &ModemPoll, last_event: &Mutex<String>) {
+    if !poll.unanswered.issues.is_empty() {
+        let issues: Vec<String> = poll.unanswered.issues.iter().map(|issue| issue.to_string()).collect();
+        *last_event.lock().unwrap() = format!("poll: {}", issues.join("; "));
+    }
+}
 
 /// Initialize the terminal
 pub fn init() -> io::Result<Tui> {
@@ -237,10 +290,21 @@ pub fn restore() -> io::Result<()> {
     disable_raw_mode()?;
     Ok(())
 }
-pub fn run_app() -> io::Result<()> {
+/// Run the TUI. Without `port_name` the user picks the port from a menu.
+/// With `record_path` all modem traffic is captured for later replay,
+/// with `history_path` every poll result is appended to a CSV or JSONL file.
+pub fn run_app(port_name: Option<String>, baud_rate: u32, record_path: Option<String>, history_path: Option<String>, gnb_id_bits: u32) -> io::Result<()> {
     errors::install_hooks().expect("TODO: panic message");
     let mut terminal = init()?;
-    let app_result = App::default().run(&mut terminal);
+    let mut app = App {
+        port_name: port_name.unwrap_or_default(),
+        baud_rate,
+        record_path,
+        history_path,
+        gnb_id_bits,
+        ..Default::default()
+    };
+    let app_result = app.run(&mut terminal);
     restore()?;
     app_result
 }
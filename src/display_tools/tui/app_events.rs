@@ -2,29 +2,41 @@ use crossterm::event;
 use crossterm::event::{Event, KeyCode, KeyEventKind};
 use crate::display_tools::tui::app::App;
 use crate::display_tools::tui::app_tabs::{BandsSelectorActive, SelectedTab};
-use crate::modem_tools::modem::{modem_execute, save_bands_command};
+use crate::modem_tools::history::timestamp;
+use crate::modem_tools::modem::modem_execute;
 
 impl App {
 
     pub(crate) fn switch_bands_selector_lists(&mut self) {
-        match self.app_tabs.selected_tab {
-            SelectedTab::BandsConfigTab => {
-                match self.app_tabs.active_bands_selector {
-                    BandsSelectorActive::UMTSBandsSelector => {
-                        self.app_tabs.umts_bands_list_state.select(None);
-                        self.app_tabs.lte_bands_list_state.select(Some(0));
-                        self.app_tabs.active_bands_selector = BandsSelectorActive::LTEBandsSelector
-                    },
-                    BandsSelectorActive::LTEBandsSelector => {
-                        self.app_tabs.lte_bands_list_state.select(None);
-                        self.app_tabs.umts_bands_list_state.select(Some(0));
-                        self.app_tabs.active_bands_selector = BandsSelectorActive::UMTSBandsSelector
-                    },
-                }
+        if let SelectedTab::BandsConfig = self.app_tabs.selected_tab {
+            match self.app_tabs.active_bands_selector {
+                BandsSelectorActive::Umts => {
+                    self.app_tabs.umts_bands_list_state.select(None);
+                    self.app_tabs.lte_bands_list_state.select(Some(0));
+                    self.app_tabs.active_bands_selector = BandsSelectorActive::Lte
+                },
+                BandsSelectorActive::Lte if self.supports_nr() => {
+                    self.app_tabs.lte_bands_list_state.select(None);
+                    self.app_tabs.nr_bands_list_state.select(Some(0));
+                    self.app_tabs.active_bands_selector = BandsSelectorActive::Nr
+                },
+                BandsSelectorActive::Lte => {
+                    self.app_tabs.lte_bands_list_state.select(None);
+                    self.app_tabs.umts_bands_list_state.select(Some(0));
+                    self.app_tabs.active_bands_selector = BandsSelectorActive::Umts
+                },
+                BandsSelectorActive::Nr => {
+                    self.app_tabs.nr_bands_list_state.select(None);
+                    self.app_tabs.umts_bands_list_state.select(Some(0));
+                    self.app_tabs.active_bands_selector = BandsSelectorActive::Umts
+                },
             }
-            _ => {}
         }
     }
+    fn supports_nr(&self) -> bool {
+        !self.app_tabs.modem_capabilities.bands.nr.is_empty()
+    }
+
     pub(crate) fn handle_events(&mut self) -> std::io::Result<()> {
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
@@ -44,7 +56,7 @@ impl App {
                     },
                     KeyCode::Esc => {
                         match self.app_tabs.selected_tab {
-                            SelectedTab::TerminalTab => {
+                            SelectedTab::Terminal => {
                                 match self.app_tabs.editing_mode {
                                     true => self.app_tabs.editing_mode = false,
                                     false => self.exit()
@@ -55,35 +67,37 @@ impl App {
                     },
                     KeyCode::Down => {
                         match self.app_tabs.active_bands_selector {
-                            BandsSelectorActive::UMTSBandsSelector => {
+                            BandsSelectorActive::Umts => {
                                 let current_selection = self.app_tabs.umts_bands_list_state.selected().unwrap_or(0);
-                                if current_selection < self.app_tabs.modem_capabilities.spec.unwrap().supported_umts_bands.len() {
+                                if current_selection < self.app_tabs.modem_capabilities.bands.umts.len() {
                                     self.app_tabs.umts_bands_list_state.select_next()
                                 }
                             },
-                            BandsSelectorActive::LTEBandsSelector => {
+                            BandsSelectorActive::Lte => {
                                 let current_selection = self.app_tabs.lte_bands_list_state.selected().unwrap_or(0);
-                                if current_selection < self.app_tabs.modem_capabilities.spec.unwrap().supported_lte_bands.len()-1 {
+                                if current_selection < self.app_tabs.modem_capabilities.bands.lte.len().saturating_sub(1) {
                                     self.app_tabs.lte_bands_list_state.select_next()
                                 }
                             }
+                            BandsSelectorActive::Nr => {
+                                let current_selection = self.app_tabs.nr_bands_list_state.selected().unwrap_or(0);
+                                if current_selection < self.app_tabs.modem_capabilities.bands.nr.len().saturating_sub(1) {
+                                    self.app_tabs.nr_bands_list_state.select_next()
+                                }
+                            }
                         }
                     },
                     KeyCode::Up => {
                         match self.app_tabs.active_bands_selector {
-                            BandsSelectorActive::UMTSBandsSelector => self.app_tabs.umts_bands_list_state.select_previous(),
-                            BandsSelectorActive::LTEBandsSelector => self.app_tabs.lte_bands_list_state.select_previous(),
-                        }
-                    },
-                    KeyCode::Backspace => {
-                        match self.app_tabs.editing_mode {
-                            true => self.delete_char(),
-                            false => {}
+                            BandsSelectorActive::Umts => self.app_tabs.umts_bands_list_state.select_previous(),
+                            BandsSelectorActive::Lte => self.app_tabs.lte_bands_list_state.select_previous(),
+                            BandsSelectorActive::Nr => self.app_tabs.nr_bands_list_state.select_previous(),
                         }
                     },
+                    KeyCode::Backspace if self.app_tabs.editing_mode => self.delete_char(),
                     KeyCode::Char(ch) => {
                         match self.app_tabs.selected_tab {
-                            SelectedTab::TerminalTab => {
+                            SelectedTab::Terminal => {
                                 match self.app_tabs.editing_mode {
                                     true => self.enter_char(ch),
                                     false => {
@@ -95,14 +109,15 @@ impl App {
                                     }
                                 }
                             },
-                            SelectedTab::BandsConfigTab => {
+                            SelectedTab::BandsConfig => {
                                 match ch {
                                     'q' | 'й' => self.exit = true,
                                     ' ' => {
                                         match self.app_tabs.active_bands_selector {
-                                            BandsSelectorActive::UMTSBandsSelector => {
+                                            BandsSelectorActive::Umts => {
                                                 let current_selection = self.app_tabs.umts_bands_list_state.selected().unwrap_or(0);
-                                                let band = self.app_tabs.modem_capabilities.spec.unwrap().supported_umts_bands[current_selection];
+                                                // Empty in generic 3GPP mode
+                                                let Some(&band) = self.app_tabs.modem_capabilities.bands.umts.get(current_selection) else { return Ok(()) };
 
                                                 if self.app_tabs.config_umts_bands.contains(&band) {
                                                     self.app_tabs.config_umts_bands.retain(|&x| x != band);
@@ -110,9 +125,9 @@ impl App {
                                                     self.app_tabs.config_umts_bands.push(band);
                                                 }
                                             },
-                                            BandsSelectorActive::LTEBandsSelector => {
+                                            BandsSelectorActive::Lte => {
                                                 let current_selection = self.app_tabs.lte_bands_list_state.selected().unwrap_or(0);
-                                                let band = self.app_tabs.modem_capabilities.spec.unwrap().supported_lte_bands[current_selection];
+                                                let Some(&band) = self.app_tabs.modem_capabilities.bands.lte.get(current_selection) else { return Ok(()) };
 
                                                 if self.app_tabs.config_lte_bands.contains(&band) {
                                                     self.app_tabs.config_lte_bands.retain(|&x| x != band);
@@ -120,48 +135,70 @@ impl App {
                                                     self.app_tabs.config_lte_bands.push(band);
                                                 }
                                             }
+                                            BandsSelectorActive::Nr => {
+                                                let current_selection = self.app_tabs.nr_bands_list_state.selected().unwrap_or(0);
+                                                let Some(&band) = self.app_tabs.modem_capabilities.bands.nr.get(current_selection) else { return Ok(()) };
+
+                                                if self.app_tabs.config_nr_bands.contains(&band) {
+                                                    self.app_tabs.config_nr_bands.retain(|&x| x != band);
+                                                } else {
+                                                    self.app_tabs.config_nr_bands.push(band);
+                                                }
+                                            }
                                         }
                                     },
                                     _ => {}
                                 }
                             }
-                            SelectedTab::MonitorTab => {
+                            SelectedTab::Monitor => {
+                                match ch {
+                                    'q' | 'й' => self.exit = true,
+                                    _ => {}
+                                }
+                            }
+                            SelectedTab::History => {
                                 match ch {
                                     'q' | 'й' => self.exit = true,
+                                    'w' | 'ц' => self.app_tabs.history_window = self.app_tabs.history_window.next(),
                                     _ => {}
                                 }
                             }
                         }
                     },
-                    KeyCode::Enter => {
-                        match self.app_tabs.editing_mode {
-                            true => {
-                                let command = self.app_tabs.terminal_data.input.trim();
-                                if command.len() > 0 {
-                                    let response = modem_execute(
-                                        &self.port_name,
-                                        &self.baud_rate,
-                                        command
-                                    ).unwrap_or_else(|err| {
-                                        eprintln!("Error executing modem command: {}", err);
-                                        "".to_string()
-                                        });
-                                    self.app_tabs.terminal_data.input = "".to_string();
-                                    self.app_tabs.terminal_data.output.push_str(response.trim());
-                                    self.app_tabs.terminal_data.output.push_str("\r\n----------\r\n");
-                                }
-                            },
-                            false => {}
+                    KeyCode::Enter if self.app_tabs.editing_mode => {
+                        let command = self.app_tabs.terminal_data.input.trim();
+                        if let (false, Some(session)) = (command.is_empty(), &self.session) {
+                            let response = match modem_execute(session, command) {
+                                Ok(response) => response.to_string(),
+                                Err(err) => format!("Error executing modem command: {}", err),
+                            };
+                            self.app_tabs.terminal_data.input = "".to_string();
+                            self.app_tabs.terminal_data.output.push_str(response.trim());
+                            self.app_tabs.terminal_data.output.push_str("\r\n----------\r\n");
                         }
                     },
+                    KeyCode::F(9) => {
+                        let path = format!("signal-history-{}.csv", timestamp() / 1000);
+                        let message = match self.app_tabs.history.lock().unwrap().save(&path) {
+                            Ok(count) => format!("{} samples saved to {}", count, path),
+                            Err(err) => format!("cannot save signal history to {}: {}", path, err),
+                        };
+                        *self.app_tabs.last_event.lock().unwrap() = message;
+                    }
                     KeyCode::F(10) => {
-                        let save_command  = save_bands_command(self.app_tabs.config_umts_bands.clone(), self.app_tabs.config_lte_bands.clone());
-                        let response = modem_execute(&self.port_name, &self.baud_rate, save_command.as_str()).unwrap_or_else(
-                            |err| {
-                                format!("{:?}", err)
-                            }
-                        );
-                        self.app_tabs.save_bands_command = response;
+                        if let Some(session) = &self.session {
+                            let profile = self.app_tabs.modem_capabilities.profile;
+                            let Some(save_command) = profile.band_lock_command(&self.app_tabs.config_umts_bands, &self.app_tabs.config_lte_bands, &self.app_tabs.config_nr_bands) else {
+                                self.app_tabs.save_bands_command = format!("{} does not support band selection", profile.name());
+                                return Ok(());
+                            };
+                            let response = match modem_execute(session, save_command.as_str()) {
+                                Ok(response) if response.is_ok() => format!("{}: {}", save_command, response),
+                                Ok(response) => response.to_string(),
+                                Err(err) => err.to_string(),
+                            };
+                            self.app_tabs.save_bands_command = response;
+                        }
                     }
                     _ => {}
                 }
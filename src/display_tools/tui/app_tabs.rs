@@ -6,6 +6,7 @@ use ratatui::style::Stylize;
 use ratatui::widgets::{ListState, Widget};
 use ratatui::text::Line;
 use strum::{Display, EnumCount, EnumIter, FromRepr};
+use crate::modem_tools::history::SignalHistory;
 use crate::modem_tools::supported_modems::Modem;
 use crate::modem_tools::types::ModemInfo;
 
@@ -13,11 +14,13 @@ use crate::modem_tools::types::ModemInfo;
 pub enum SelectedTab {
     #[default]
     #[strum(to_string = "Signal monitor")]
-    MonitorTab,
+    Monitor,
     #[strum(to_string = "Select bands")]
-    BandsConfigTab,
+    BandsConfig,
     #[strum(to_string = "Terminal")]
-    TerminalTab,
+    Terminal,
+    #[strum(to_string = "Signal history")]
+    History,
 }
 
 impl SelectedTab {
@@ -31,9 +34,10 @@ impl SelectedTab {
 
     pub const fn palette(self) -> tailwind::Palette {
         match self {
-            Self::MonitorTab => tailwind::EMERALD,
-            Self::BandsConfigTab => tailwind::INDIGO,
-            Self::TerminalTab => tailwind::RED,
+            Self::Monitor => tailwind::EMERALD,
+            Self::BandsConfig => tailwind::INDIGO,
+            Self::Terminal => tailwind::RED,
+            Self::History => tailwind::SKY,
         }
     }
 }
@@ -50,26 +54,71 @@ pub struct TerminalData {
     pub output: String
 }
 
+/// Time span shown on the history tab
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum HistoryWindow {
+    FiveMinutes,
+    #[default]
+    FifteenMinutes,
+    OneHour,
+    All,
+}
+
+impl HistoryWindow {
+    pub fn seconds(self) -> Option<u64> {
+        match self {
+            Self::FiveMinutes => Some(5 * 60),
+            Self::FifteenMinutes => Some(15 * 60),
+            Self::OneHour => Some(60 * 60),
+            Self::All => None,
+        }
+    }
+
+    pub fn next(self) -> HistoryWindow {
+        match self {
+            Self::FiveMinutes => Self::FifteenMinutes,
+            Self::FifteenMinutes => Self::OneHour,
+            Self::OneHour => Self::All,
+            Self::All => Self::FiveMinutes,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::FiveMinutes => "5 min",
+            Self::FifteenMinutes => "15 min",
+            Self::OneHour => "1 hour",
+            Self::All => "all",
+        }
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 pub enum BandsSelectorActive {
     #[default]
-    UMTSBandsSelector,
-    LTEBandsSelector,
+    Umts,
+    Lte,
+    Nr,
 }
 
 #[derive(Default, Clone)]
 pub struct AppTabs {
     pub selected_tab: SelectedTab,
     pub modem_info: Arc<Mutex<ModemInfo>>,
+    pub last_event: Arc<Mutex<String>>,
+    pub history: Arc<Mutex<SignalHistory>>,
+    pub history_window: HistoryWindow,
     pub active_bands_selector: BandsSelectorActive,
     pub umts_bands_list_state: ListState,
     pub lte_bands_list_state: ListState,
+    pub nr_bands_list_state: ListState,
     pub editing_mode: bool,
     pub cursor_index: usize,
     pub cursor_position: CursorPosition,
     pub terminal_data: TerminalData,
     pub config_lte_bands: Vec<usize>,
     pub config_umts_bands: Vec<usize>,
+    pub config_nr_bands: Vec<usize>,
     pub save_bands_command: String,
     pub(crate) modem_capabilities: Modem,
 }
@@ -100,9 +149,10 @@ impl Widget for AppTabs {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // in a real app these might be separate widgets
         match self.selected_tab {
-            SelectedTab::MonitorTab => self.render_monitor_tab(area, buf),
-            SelectedTab::BandsConfigTab => self.render_config_tab(area, buf),
-            SelectedTab::TerminalTab => self.render_terminal_tab(area, buf),
+            SelectedTab::Monitor => self.render_monitor_tab(area, buf),
+            SelectedTab::BandsConfig => self.render_config_tab(area, buf),
+            SelectedTab::Terminal => self.render_terminal_tab(area, buf),
+            SelectedTab::History => self.render_history_tab(area, buf),
         }
     }
 }
\ No newline at end of file
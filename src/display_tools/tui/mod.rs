@@ -1,4 +1,5 @@
 mod port_choice_menu;
+mod unsupported_modem;
 pub mod app;
 pub mod app_tabs;
 mod errors;
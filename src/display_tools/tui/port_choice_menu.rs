@@ -18,6 +18,8 @@ pub fn show_port_choice_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>)
     // let port_names = list_ports().unwrap().iter().map(|port| port.port_name );
     let mut list_state = ListState::default();
     list_state.select(Some(0));
+    // Network endpoint typed by the user, e.g. tcp://192.168.1.1:2000
+    let mut address: Option<String> = None;
 
     loop {
         terminal.draw(|f| {
@@ -45,16 +47,38 @@ pub fn show_port_choice_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>)
 
             f.render_stateful_widget(ports_list, chunks[0], &mut list_state);
 
-            let instructions = Paragraph::new("Use Up/Down arrows to select a port, Enter to confirm, Q to quit.")
-                .style(Style::default().fg(Color::White).bg(Color::Black));
-            f.render_widget(instructions, chunks[1]);
+            match &address {
+                Some(address) => {
+                    let input = Paragraph::new(address.as_str())
+                        .style(Style::default().fg(Color::Yellow))
+                        .block(Block::default().title("Address (tcp://host:port or unix:///path), Enter to connect, Esc to cancel").borders(Borders::ALL));
+                    f.render_widget(input, chunks[1]);
+                }
+                None => {
+                    let instructions = Paragraph::new("Use Up/Down arrows to select a port, Enter to confirm, A to enter a network address, Q to quit.")
+                        .style(Style::default().fg(Color::White).bg(Color::Black));
+                    f.render_widget(instructions, chunks[1]);
+                }
+            }
         }).ok();
 
         if event::poll(Duration::from_millis(100)).unwrap() {
             if let Ok(Key(key)) = event::read() {
                 if key.kind == KeyEventKind::Press {
+                    if let Some(input) = address.as_mut() {
+                        match key.code {
+                            KeyCode::Char(ch) => input.push(ch),
+                            KeyCode::Backspace => { input.pop(); },
+                            KeyCode::Esc => address = None,
+                            KeyCode::Enter if !input.trim().is_empty() => return Ok(input.trim().to_string()),
+                            _ => {}
+                        }
+                        continue;
+                    }
                     match key.code {
                         KeyCode::Char('q') => return Ok("".to_string()),
+                        KeyCode::Char('a') => address = Some("tcp://".to_string()),
+                        KeyCode::Down | KeyCode::Up if port_names.is_empty() => {}
                         KeyCode::Down => {
                             let i = match list_state.selected() {
                                 Some(i) => {
@@ -82,8 +106,8 @@ pub fn show_port_choice_menu(terminal: &mut Terminal<CrosstermBackend<Stdout>>)
                             list_state.select(Some(i));
                         }
                         KeyCode::Enter => {
-                            if let Some(i) = list_state.selected() {
-                                return Ok(port_names[i].clone());
+                            if let Some(port_name) = list_state.selected().and_then(|i| port_names.get(i)) {
+                                return Ok(port_name.clone());
                             }
                         }
                         _ => {}
@@ -9,6 +9,11 @@ impl AppTabs {
     pub fn render_config_tab(self, area: Rect, buf: &mut Buffer) {
         let mut umts_bands_list_state = self.umts_bands_list_state.clone();
         let mut lte_bands_list_state = self.lte_bands_list_state.clone();
+        let mut nr_bands_list_state = self.nr_bands_list_state.clone();
+        let supported = &self.modem_capabilities.bands;
+        let lte_4x4_mimo_bands = self.modem_capabilities.profile.lte_4x4_mimo_bands();
+        // The NR list is only shown for 5G modems
+        let lists = if supported.nr.is_empty() { 2 } else { 3 };
 
         let tab_area = Layout::default()
             .direction(Direction::Vertical)
@@ -20,22 +25,15 @@ impl AppTabs {
 
         let bands_lists_area = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage(50),
-                    Constraint::Percentage(50),
-                ]
-                .as_ref(),
-            )
+            .constraints(vec![Constraint::Ratio(1, lists); lists as usize])
             .split(tab_area[0]);
 
         let enabled_lte_bands: HashSet<_> =  self.config_lte_bands.iter().collect();
         let enabled_umts_bands: HashSet<_> = self.config_umts_bands.iter().collect();
-
-        let modem_caps = self.modem_capabilities.spec.unwrap();
+        let enabled_nr_bands: HashSet<_> = self.config_nr_bands.iter().collect();
 
         let umts_bands_list = List::new(
-                modem_caps.supported_umts_bands.iter().map(|b| {
+                supported.umts.iter().map(|b| {
                     let band_str = format!("B{}", b);
                     let checkbox = if enabled_umts_bands.contains(&b) {
                         "[x]"
@@ -53,8 +51,12 @@ impl AppTabs {
             .direction(ListDirection::TopToBottom);
 
         let lte_bands_list = List::new(
-                modem_caps.supported_lte_bands.iter().map(|b| {
-                    let band_str = format!("B{}", b);
+                supported.lte.iter().map(|b| {
+                    let band_str = if lte_4x4_mimo_bands.contains(b) {
+                        format!("B{} 4x4", b)
+                    } else {
+                        format!("B{}", b)
+                    };
                     let checkbox = if enabled_lte_bands.contains(&b) {
                         "[x]"
                     } else {
@@ -70,12 +72,32 @@ impl AppTabs {
             .repeat_highlight_symbol(true)
             .direction(ListDirection::TopToBottom);
 
+        let nr_bands_list = List::new(
+                supported.nr.iter().map(|b| {
+                    let checkbox = if enabled_nr_bands.contains(&b) {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    };
+                    ListItem::new(format!("{} n{}", checkbox, b))
+                }).collect::<Vec<_>>()
+            )
+            .block(Block::bordered().borders(Borders::ALL).title("NR Bands List"))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .highlight_style(Style::default().bg(Color::Gray).fg(Color::Black).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")
+            .repeat_highlight_symbol(true)
+            .direction(ListDirection::TopToBottom);
+
         let command_to_execute = Paragraph::new(self.save_bands_command.clone())
             .style(Style::default().fg(Color::White))
             .block(Block::default().title("AT command").borders(Borders::ALL));
 
         StatefulWidget::render(umts_bands_list, bands_lists_area[0], buf, &mut umts_bands_list_state);
         StatefulWidget::render(lte_bands_list, bands_lists_area[1], buf, &mut lte_bands_list_state);
+        if lists == 3 {
+            StatefulWidget::render(nr_bands_list, bands_lists_area[2], buf, &mut nr_bands_list_state);
+        }
         Widget::render(command_to_execute, tab_area[1], buf);
     }
 }
@@ -0,0 +1,145 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::prelude::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Sparkline, Widget};
+use crate::display_tools::tui::app_tabs::AppTabs;
+use crate::modem_tools::history::{cell_changes, CellChange, HistorySample};
+
+// Most recent cell changes listed under the charts
+const LISTED_CHANGES: usize = 3;
+
+struct Metric {
+    title: &'static str,
+    unit: &'static str,
+    color: Color,
+    value: fn(&HistorySample) -> Option<i32>,
+}
+
+const METRICS: [Metric; 4] = [
+    Metric { title: "RSRP", unit: "dBm", color: Color::Green, value: |s| s.rsrp_dbm },
+    Metric { title: "RSRQ", unit: "dB", color: Color::Cyan, value: |s| s.rsrq_db },
+    Metric { title: "SINR", unit: "dB", color: Color::Magenta, value: |s| s.sinr_db },
+    Metric { title: "RSSI", unit: "dBm", color: Color::Blue, value: |s| s.rssi_dbm },
+];
+
+/// Seconds relative to the newest sample, so the right edge of a chart is "now".
+fn seconds_ago(sample: &HistorySample, newest: u64) -> f64 {
+    (sample.timestamp_ms as f64 - newest as f64) / 1000.0
+}
+
+fn format_seconds(seconds: f64) -> String {
+    match -seconds {
+        s if s >= 3600.0 => format!("-{:.1}h", s / 3600.0),
+        s if s >= 60.0 => format!("-{:.0}m", s / 60.0),
+        s if s > 0.0 => format!("-{:.0}s", s),
+        _ => "now".to_string(),
+    }
+}
+
+fn render_metric(metric: &Metric, samples: &[&HistorySample], changes: &[CellChange], x_bounds: [f64; 2], newest: u64, area: Rect, buf: &mut Buffer) {
+    // Polls the metric is missing from leave a gap instead of a drop to zero
+    let points: Vec<(f64, f64)> = samples.iter()
+        .filter_map(|sample| Some((seconds_ago(sample, newest), (metric.value)(sample)? as f64)))
+        .collect();
+
+    let min = points.iter().map(|&(_, y)| y).fold(f64::INFINITY, f64::min);
+    let max = points.iter().map(|&(_, y)| y).fold(f64::NEG_INFINITY, f64::max);
+    let y_bounds = if points.is_empty() { [-1.0, 1.0] } else { [min.floor() - 3.0, max.ceil() + 3.0] };
+
+    // A vertical line across the whole chart at every serving cell or band change
+    let markers: Vec<[(f64, f64); 2]> = changes.iter()
+        .map(|change| {
+            let x = seconds_ago(change.to, newest);
+            [(x, y_bounds[0]), (x, y_bounds[1])]
+        })
+        .collect();
+
+    let mut datasets = vec![
+        Dataset::default()
+            .name(metric.title)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(metric.color))
+            .data(&points),
+    ];
+    datasets.extend(markers.iter().map(|marker| {
+        Dataset::default()
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(marker)
+    }));
+
+    let latest = points.last().map_or("--".to_string(), |&(_, y)| format!("{}{}", y, metric.unit));
+    Chart::new(datasets)
+        .block(Block::default().title(format!("{} {}", metric.title, latest)).borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .bounds(x_bounds)
+            .labels([format_seconds(x_bounds[0]), "now".to_string()]))
+        .y_axis(Axis::default()
+            .bounds(y_bounds)
+            .labels([y_bounds[0].to_string(), y_bounds[1].to_string()]))
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .render(area, buf);
+}
+
+impl AppTabs {
+    pub fn render_history_tab(self, area: Rect, buf: &mut Buffer) {
+        let v_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+                Constraint::Min(3),
+                Constraint::Length(LISTED_CHANGES as u16 + 2),
+            ].as_ref())
+            .split(area);
+        let rows = [v_chunks[0], v_chunks[1]].map(|row| Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(row));
+
+        let history = self.history.lock().unwrap();
+        let newest = history.samples().last().map_or(0, |sample| sample.timestamp_ms);
+        let since = self.history_window.seconds().map_or(0, |window| newest.saturating_sub(window * 1000));
+        let samples: Vec<&HistorySample> = history.samples().filter(|sample| sample.timestamp_ms >= since).collect();
+        let changes = cell_changes(&samples);
+
+        let oldest = match self.history_window.seconds() {
+            Some(window) => -(window as f64),
+            None => samples.first().map_or(-60.0, |sample| seconds_ago(sample, newest).min(-60.0)),
+        };
+        let x_bounds = [oldest, 0.0];
+
+        let areas = [rows[0][0], rows[0][1], rows[1][0], rows[1][1]];
+        for (metric, area) in METRICS.iter().zip(areas) {
+            render_metric(metric, &samples, &changes, x_bounds, newest, area, buf);
+        }
+
+        // 99: not known or not detectable
+        let csq: Vec<u64> = samples.iter()
+            .map(|sample| sample.csq.filter(|csq| (0..=31).contains(csq)).unwrap_or(0) as u64)
+            .collect();
+        // Keep the newest values when there are more samples than columns
+        let columns = v_chunks[2].width.saturating_sub(2) as usize;
+        Sparkline::default()
+            .block(Block::default().title("CSQ").borders(Borders::ALL))
+            .data(&csq[csq.len().saturating_sub(columns)..])
+            .max(31)
+            .style(Style::default().fg(Color::Green).bg(Color::Black))
+            .render(v_chunks[2], buf);
+
+        let listed: Vec<Line> = changes.iter().rev().take(LISTED_CHANGES)
+            .map(|change| Line::from(format!("{:>6}  {}", format_seconds(seconds_ago(change.to, newest)), change.describe())))
+            .collect();
+        Paragraph::new(listed)
+            .block(Block::default()
+                .title("Cell changes")
+                .title_bottom(format!("Window: {} (w to change) | {} samples", self.history_window.label(), samples.len()))
+                .borders(Borders::ALL))
+            .style(Style::default().fg(Color::Yellow).bg(Color::Black))
+            .render(v_chunks[3], buf);
+    }
+}
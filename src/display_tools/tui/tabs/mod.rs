@@ -1,3 +1,4 @@
 pub mod monitor_tab;
 mod config_tab;
-mod terminal_tab;
\ No newline at end of file
+mod terminal_tab;
+mod history_tab;
\ No newline at end of file
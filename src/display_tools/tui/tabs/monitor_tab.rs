@@ -1,8 +1,27 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::prelude::{Color, Style, Text};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Widget};
 use crate::display_tools::tui::app_tabs::AppTabs;
+use crate::modem_tools::parse_report::ParseReport;
+
+// Responses each section is read from, across the supported profiles
+const MODEM_INFO_RESPONSES: [&str; 7] = ["+CGMI", "+CGMM", "+CGMR", "+FMM", "+GTPKGVER", "+CFSN", "+CGSN"];
+const SIGNAL_INFO_RESPONSES: [&str; 14] = ["+CREG", "+CGREG", "+CEREG", "+C5GREG", "+CIMI", "+CCID", "+COPS", "+CGCONTRDP", "+CGPADDR", "+CSQ", "+CESQ", "+XLEC", "+XACT", "+GTACT"];
+const CARRIER_INFO_RESPONSES: [&str; 3] = ["+XMCI", "+GTCCINFO", "+GTCAINFO"];
+
+/// Block title followed, in red, by the responses of the section that could not be read
+fn section_title<'a>(title: &'a str, report: &ParseReport, responses: &[&str]) -> Line<'a> {
+    let issues: Vec<String> = report.issues_for(responses).map(|issue| issue.to_string()).collect();
+    if issues.is_empty() {
+        return Line::from(title);
+    }
+    Line::from(vec![
+        Span::raw(title),
+        Span::styled(format!(" [{}]", issues.join(", ")), Style::default().fg(Color::Red)),
+    ])
+}
 
 impl AppTabs {
         pub fn render_monitor_tab(self, area: Rect, buf: &mut Buffer) {
@@ -22,15 +41,17 @@ impl AppTabs {
         let info = self.modem_info.lock().unwrap();
 
         Paragraph::new(Text::from(info.display_modem_info()))
-            .block(Block::default().title("Modem Info").borders(Borders::ALL))
+            .block(Block::default().title(section_title("Modem Info", &info.parse_report, &MODEM_INFO_RESPONSES)).borders(Borders::ALL))
             .style(Style::default().fg(Color::White).bg(Color::Black)).render(chunks[0], buf);
 
         Paragraph::new(Text::from(info.display_signal_info()))
-            .block(Block::default().title("Signal Info").borders(Borders::ALL))
+            .block(Block::default().title(section_title("Signal Info", &info.parse_report, &SIGNAL_INFO_RESPONSES)).borders(Borders::ALL))
             .style(Style::default().fg(Color::White).bg(Color::Black)).render(chunks[1], buf);
 
+        let last_event = self.last_event.lock().unwrap();
+
         Paragraph::new(Text::from(info.display_carrier_info()))
-            .block(Block::default().title("Carrier Info").borders(Borders::ALL))
+            .block(Block::default().title(section_title("Carrier Info", &info.parse_report, &CARRIER_INFO_RESPONSES)).title_bottom(format!("Last event: {}", last_event)).borders(Borders::ALL))
             .style(Style::default().fg(Color::White).bg(Color::Black)).render(chunks[2], buf);
     }
 }
\ No newline at end of file
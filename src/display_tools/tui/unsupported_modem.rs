@@ -0,0 +1,60 @@
+use std::io::Stdout;
+use std::time::Duration;
+use crossterm::event;
+use crossterm::event::Event::Key;
+use crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::prelude::{Color, Modifier, Style};
+use ratatui::Terminal;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use crate::modem_tools::supported_modems::{ModemIdentity, PROFILES};
+
+/// Shown when no profile matches the connected modem. Returns `false` when the user quits.
+pub fn show_unsupported_modem_screen(terminal: &mut Terminal<CrosstermBackend<Stdout>>, identity: &ModemIdentity) -> Result<bool, std::io::Error> {
+    let supported: Vec<&str> = PROFILES.iter().map(|profile| profile.name()).collect();
+    let field = |value: &str| if value.is_empty() { "unknown".to_string() } else { value.to_string() };
+
+    loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(90), Constraint::Percentage(10)].as_ref())
+                .split(f.area());
+
+            let text = vec![
+                Line::styled("Unsupported modem, generic 3GPP mode", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Line::from(""),
+                Line::from(format!("Manufacturer: {}", field(&identity.manufacturer))),
+                Line::from(format!("Model:        {}", field(&identity.model))),
+                Line::from(format!("Revision:     {}", field(&identity.revision))),
+                Line::from(""),
+                Line::from("No modem profile matches this modem. It is monitored with standard 3GPP TS 27.007 commands only:"),
+                Line::from("operator, registration, CSQ, RSRP/RSRQ and address, without carrier aggregation, neighbour cells or band selection."),
+                Line::from(""),
+                Line::from(format!("Supported modems: {}", supported.join(", "))),
+            ];
+            let screen = Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title("Modem detection").borders(Borders::ALL));
+            f.render_widget(screen, chunks[0]);
+
+            let instructions = Paragraph::new("Enter to continue in generic 3GPP mode, Q to quit.")
+                .style(Style::default().fg(Color::White).bg(Color::Black));
+            f.render_widget(instructions, chunks[1]);
+        })?;
+
+        if event::poll(Duration::from_millis(100))? {
+            if let Ok(Key(key)) = event::read() {
+                if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                        KeyCode::Enter => return Ok(true),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
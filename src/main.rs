@@ -1,13 +1,13 @@
+mod cli;
 mod serial_tools;
 mod modem_tools;
 mod display_tools;
 mod tests;
 
-use std::io;
-use display_tools::tui::app::run_app;
+use std::process::ExitCode;
+use clap::Parser;
+use cli::Cli;
 
-fn main() -> Result<(), io::Error> {
-    run_app()?;
-
-    Ok(())
+fn main() -> ExitCode {
+    cli::run(Cli::parse())
 }
@@ -0,0 +1,395 @@
+use std::fmt;
+use std::io;
+use crate::modem_tools::urc::Urc;
+
+/// Final result code that terminates the response to an AT command
+/// (3GPP TS 27.007 clause 4.2, TS 27.005 clause 3.2.5).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FinalResult {
+    Ok,
+    Connect(Option<String>),
+    Error,
+    CmeError(u16),
+    CmsError(u16),
+    NoCarrier,
+    NoDialtone,
+    Busy,
+    NoAnswer,
+}
+
+impl FinalResult {
+    /// Recognise a complete response line as a final result code.
+    pub fn parse(line: &str) -> Option<FinalResult> {
+        let line = line.trim();
+        match line {
+            "OK" => return Some(FinalResult::Ok),
+            "ERROR" => return Some(FinalResult::Error),
+            "NO CARRIER" => return Some(FinalResult::NoCarrier),
+            "NO DIALTONE" => return Some(FinalResult::NoDialtone),
+            "BUSY" => return Some(FinalResult::Busy),
+            "NO ANSWER" => return Some(FinalResult::NoAnswer),
+            "CONNECT" => return Some(FinalResult::Connect(None)),
+            _ => {}
+        }
+
+        if let Some(text) = line.strip_prefix("CONNECT ") {
+            return Some(FinalResult::Connect(Some(text.to_string())));
+        }
+        if let Some(error) = line.strip_prefix("+CME ERROR:") {
+            return Some(FinalResult::CmeError(parse_error_code(error, CME_ERRORS, 100)));
+        }
+        if let Some(error) = line.strip_prefix("+CMS ERROR:") {
+            return Some(FinalResult::CmsError(parse_error_code(error, CMS_ERRORS, 500)));
+        }
+        None
+    }
+
+    pub fn is_ok(&self) -> bool {
+        matches!(self, FinalResult::Ok | FinalResult::Connect(_))
+    }
+
+    /// Human-readable explanation of a `+CME ERROR`/`+CMS ERROR` code.
+    pub fn description(&self) -> Option<&'static str> {
+        match self {
+            FinalResult::CmeError(code) => Some(cme_error_text(*code)),
+            FinalResult::CmsError(code) => Some(cms_error_text(*code)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FinalResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FinalResult::Ok => write!(f, "OK"),
+            FinalResult::Connect(None) => write!(f, "CONNECT"),
+            FinalResult::Connect(Some(text)) => write!(f, "CONNECT {}", text),
+            FinalResult::Error => write!(f, "ERROR"),
+            FinalResult::CmeError(code) => write!(f, "+CME ERROR: {}", code),
+            FinalResult::CmsError(code) => write!(f, "+CMS ERROR: {}", code),
+            FinalResult::NoCarrier => write!(f, "NO CARRIER"),
+            FinalResult::NoDialtone => write!(f, "NO DIALTONE"),
+            FinalResult::Busy => write!(f, "BUSY"),
+            FinalResult::NoAnswer => write!(f, "NO ANSWER"),
+        }
+    }
+}
+
+/// Complete response to one AT command: the information lines without the
+/// command echo, and the final result code that ended it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtResponse {
+    pub lines: Vec<String>,
+    pub result: FinalResult,
+}
+
+impl AtResponse {
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Response in the modem wire format, one line per `\r\n`.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            text.push_str(line);
+            text.push_str("\r\n");
+        }
+        text.push_str(&self.result.to_string());
+        text.push_str("\r\n");
+        text
+    }
+}
+
+impl fmt::Display for AtResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        match self.result.description() {
+            Some(description) => write!(f, "{} ({})", self.result, description),
+            None => write!(f, "{}", self.result),
+        }
+    }
+}
+
+/// Splits the raw byte stream coming from the modem into lines. Lines
+/// belonging to the command in flight are collected until its final result
+/// code arrives; everything else is kept aside as unsolicited result codes.
+#[derive(Default)]
+pub struct ResponseReader {
+    command: Option<String>,
+    pending: Vec<u8>,
+    lines: Vec<String>,
+    unsolicited: Vec<Urc>,
+    /// Command that timed out, its answer may still arrive
+    stale: Option<String>,
+}
+
+impl ResponseReader {
+    /// Start collecting the response to `command`.
+    pub fn expect(&mut self, command: &str) {
+        self.command = Some(command.trim().to_string());
+        self.lines.clear();
+    }
+
+    /// Give up on the command in flight. Whatever the modem still sends for it, up to
+    /// its final result code, is dropped instead of being taken for the next answer.
+    pub fn abandon(&mut self) {
+        self.stale = self.command.take();
+        self.pending.clear();
+        self.lines.clear();
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale.is_some()
+    }
+
+    /// Consume the next chunk read from the port. Returns the response as soon
+    /// as its final result code has been received.
+    pub fn feed(&mut self, data: &[u8]) -> Option<AtResponse> {
+        self.pending.extend_from_slice(data);
+
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\r' || b == b'\n') {
+            let raw: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&raw[..end]).trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(stale) = self.stale.as_deref() {
+                // The echo of the new command shows the modem is done with the old one
+                if FinalResult::parse(&line).is_some() || self.command.as_deref() == Some(line.as_str()) {
+                    self.stale = None;
+                } else if let Some(urc) = Urc::parse(&line, Some(stale)) {
+                    self.unsolicited.push(urc);
+                }
+                continue;
+            }
+
+            let Some(command) = self.command.as_deref() else {
+                // A stray final result code while idle carries no information
+                if FinalResult::parse(&line).is_none() {
+                    self.unsolicited.push(Urc::unsolicited(&line));
+                }
+                continue;
+            };
+
+            if line == command {
+                continue;
+            }
+            if let Some(result) = FinalResult::parse(&line) {
+                self.command = None;
+                return Some(AtResponse {
+                    lines: std::mem::take(&mut self.lines),
+                    result,
+                });
+            }
+            match Urc::parse(&line, Some(command)) {
+                Some(urc) => self.unsolicited.push(urc),
+                None => self.lines.push(line),
+            }
+        }
+        None
+    }
+
+    pub fn take_unsolicited(&mut self) -> Vec<Urc> {
+        std::mem::take(&mut self.unsolicited)
+    }
+}
+
+/// Failure to get any final result code for a command.
+#[derive(Debug)]
+pub enum AtError {
+    Io(io::Error),
+    Timeout(String),
+    SessionClosed,
+}
+
+impl fmt::Display for AtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtError::Io(err) => write!(f, "{}", err),
+            AtError::Timeout(command) => write!(f, "no response to {}", command),
+            AtError::SessionClosed => write!(f, "modem session is closed"),
+        }
+    }
+}
+
+impl std::error::Error for AtError {}
+
+impl From<io::Error> for AtError {
+    fn from(err: io::Error) -> Self {
+        AtError::Io(err)
+    }
+}
+
+fn parse_error_code(error: &str, table: &[(u16, &str)], unknown: u16) -> u16 {
+    let error = error.trim();
+    if let Ok(code) = error.parse::<u16>() {
+        return code;
+    }
+    // Verbose mode (AT+CMEE=2) reports the text instead of the number
+    table.iter()
+        .find(|(_, text)| text.eq_ignore_ascii_case(error))
+        .map_or(unknown, |(code, _)| *code)
+}
+
+pub fn cme_error_text(code: u16) -> &'static str {
+    lookup(CME_ERRORS, code).unwrap_or("unknown error")
+}
+
+pub fn cms_error_text(code: u16) -> &'static str {
+    lookup(CMS_ERRORS, code).unwrap_or("unknown error")
+}
+
+fn lookup(table: &[(u16, &'static str)], code: u16) -> Option<&'static str> {
+    table.iter().find(|(c, _)| *c == code).map(|(_, text)| *text)
+}
+
+// 3GPP TS 27.007 clause 9.2
+static CME_ERRORS: &[(u16, &str)] = &[
+    (0, "phone failure"),
+    (1, "no connection to phone"),
+    (2, "phone-adaptor link reserved"),
+    (3, "operation not allowed"),
+    (4, "operation not supported"),
+    (5, "PH-SIM PIN required"),
+    (6, "PH-FSIM PIN required"),
+    (7, "PH-FSIM PUK required"),
+    (10, "SIM not inserted"),
+    (11, "SIM PIN required"),
+    (12, "SIM PUK required"),
+    (13, "SIM failure"),
+    (14, "SIM busy"),
+    (15, "SIM wrong"),
+    (16, "incorrect password"),
+    (17, "SIM PIN2 required"),
+    (18, "SIM PUK2 required"),
+    (20, "memory full"),
+    (21, "invalid index"),
+    (22, "not found"),
+    (23, "memory failure"),
+    (24, "text string too long"),
+    (25, "invalid characters in text string"),
+    (26, "dial string too long"),
+    (27, "invalid characters in dial string"),
+    (30, "no network service"),
+    (31, "network timeout"),
+    (32, "network not allowed - emergency calls only"),
+    (40, "network personalization PIN required"),
+    (41, "network personalization PUK required"),
+    (42, "network subset personalization PIN required"),
+    (43, "network subset personalization PUK required"),
+    (44, "service provider personalization PIN required"),
+    (45, "service provider personalization PUK required"),
+    (46, "corporate personalization PIN required"),
+    (47, "corporate personalization PUK required"),
+    (48, "hidden key required"),
+    (49, "EAP method not supported"),
+    (50, "incorrect parameters"),
+    (51, "command implemented but currently disabled"),
+    (52, "command aborted by user"),
+    (53, "not attached to network due to MT functionality restrictions"),
+    (54, "modem not allowed - MT restricted to emergency calls only"),
+    (55, "operation not allowed because of MT functionality restrictions"),
+    (56, "fixed dial number only allowed"),
+    (57, "temporarily out of service due to other MT usage"),
+    (58, "language/alphabet not supported"),
+    (59, "unexpected data value"),
+    (60, "system failure"),
+    (61, "data missing"),
+    (62, "call barred"),
+    (63, "message waiting indication subscription failure"),
+    (100, "unknown"),
+    (103, "illegal MS"),
+    (106, "illegal ME"),
+    (107, "GPRS services not allowed"),
+    (108, "GPRS services and non-GPRS services not allowed"),
+    (111, "PLMN not allowed"),
+    (112, "location area not allowed"),
+    (113, "roaming not allowed in this location area"),
+    (114, "GPRS services not allowed in this PLMN"),
+    (115, "no suitable cells in location area"),
+    (122, "congestion"),
+    (125, "not authorized for this CSG"),
+    (126, "insufficient resources"),
+    (127, "missing or unknown APN"),
+    (128, "unknown PDP address or PDP type"),
+    (129, "user authentication failed"),
+    (130, "activation rejected by GGSN, Serving GW or PDN GW"),
+    (131, "activation rejected, unspecified"),
+    (132, "service option not supported"),
+    (133, "requested service option not subscribed"),
+    (134, "service option temporarily out of order"),
+    (140, "feature not supported"),
+    (141, "semantic error in the TFT operation"),
+    (142, "syntactical error in the TFT operation"),
+    (143, "unknown PDP context"),
+    (144, "semantic errors in packet filter(s)"),
+    (145, "syntactical errors in packet filter(s)"),
+    (146, "PDP context without TFT already activated"),
+    (148, "unspecified GPRS error"),
+    (149, "PDP authentication failure"),
+    (150, "invalid mobile class"),
+    (171, "last PDN disconnection not allowed"),
+    (172, "semantically incorrect message"),
+    (173, "invalid mandatory information"),
+    (174, "message type non-existent or not implemented"),
+    (175, "conditional IE error"),
+    (176, "unspecified protocol error"),
+    (177, "operator determined barring"),
+    (178, "maximum number of PDP contexts reached"),
+    (179, "requested APN not supported in current RAT and PLMN combination"),
+    (180, "request rejected, bearer control mode violation"),
+    (181, "unsupported QCI value"),
+];
+
+// 3GPP TS 27.005 clause 3.2.5, values below 256 are network causes from TS 24.011
+static CMS_ERRORS: &[(u16, &str)] = &[
+    (1, "unassigned (unallocated) number"),
+    (8, "operator determined barring"),
+    (10, "call barred"),
+    (21, "short message transfer rejected"),
+    (27, "destination out of service"),
+    (28, "unidentified subscriber"),
+    (29, "facility rejected"),
+    (30, "unknown subscriber"),
+    (38, "network out of order"),
+    (41, "temporary failure"),
+    (42, "congestion"),
+    (47, "resources unavailable, unspecified"),
+    (50, "requested facility not subscribed"),
+    (69, "requested facility not implemented"),
+    (81, "invalid short message transfer reference value"),
+    (95, "invalid message, unspecified"),
+    (96, "invalid mandatory information"),
+    (97, "message type non-existent or not implemented"),
+    (98, "message not compatible with short message protocol state"),
+    (99, "information element non-existent or not implemented"),
+    (111, "protocol error, unspecified"),
+    (127, "interworking, unspecified"),
+    (300, "ME failure"),
+    (301, "SMS service of ME reserved"),
+    (302, "operation not allowed"),
+    (303, "operation not supported"),
+    (304, "invalid PDU mode parameter"),
+    (305, "invalid text mode parameter"),
+    (310, "(U)SIM not inserted"),
+    (311, "(U)SIM PIN required"),
+    (312, "PH-(U)SIM PIN required"),
+    (313, "(U)SIM failure"),
+    (314, "(U)SIM busy"),
+    (315, "(U)SIM wrong"),
+    (316, "(U)SIM PUK required"),
+    (317, "(U)SIM PIN2 required"),
+    (318, "(U)SIM PUK2 required"),
+    (320, "memory failure"),
+    (321, "invalid memory index"),
+    (322, "memory full"),
+    (330, "SMSC address unknown"),
+    (331, "no network service"),
+    (332, "network timeout"),
+    (340, "no +CNMA acknowledgement expected"),
+    (500, "unknown error"),
+];
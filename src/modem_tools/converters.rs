@@ -1,3 +1,7 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use serde::Serialize;
+
 pub fn parse_bandwidth(bandwidth: &str) -> String {
     bandwidth.split(',')
         .map(|bw| match bw {
@@ -13,6 +17,19 @@ pub fn parse_bandwidth(bandwidth: &str) -> String {
         .join(", ")
 }
 
+/// Channel bandwidth in MHz of a `+XLEC`/`+GTCCINFO` bandwidth code
+pub fn bandwidth_mhz(bandwidth: &str) -> Option<f64> {
+    match bandwidth.trim() {
+        "0" => Some(1.4),
+        "1" => Some(3.0),
+        "2" => Some(5.0),
+        "3" => Some(10.0),
+        "4" => Some(15.0),
+        "5" => Some(20.0),
+        _ => None,
+    }
+}
+
 pub fn convert_rsrp_to_rssi(rsrp:i32, bandwidth: i32) -> Option<i32> {
     let np = match bandwidth {
         0 => 6,
@@ -27,61 +44,481 @@ pub fn convert_rsrp_to_rssi(rsrp:i32, bandwidth: i32) -> Option<i32> {
     if np > 0 {
         Some((rsrp as f64 + 10.0 * ((12 * np) as f64).log10())as i32)
     } else {
-        Some(-113)
-    }
-}
-
-
-pub fn get_band_lte(earfcn: i32) -> &'static str {
-    match earfcn {
-        0..=599 => "B1",
-        600..=1199 => "B2",
-        1200..=1949 => "B3",
-        1950..=2399 => "B4",
-        2400..=2649 => "B5",
-        2750..=3449 => "B7",
-        3450..=3799 => "B8",
-        3800..=4149 => "B9",
-        4150..=4749 => "B10",
-        4750..=4949 => "B11",
-        5010..=5179 => "B12",
-        5180..=5279 => "B13",
-        5280..=5379 => "B14",
-        5730..=5849 => "B17",
-        5850..=5999 => "B18",
-        6000..=6149 => "B19",
-        6150..=6449 => "B20",
-        6450..=6599 => "B21",
-        6600..=7399 => "B22",
-        7500..=7699 => "B23",
-        7700..=8039 => "B24",
-        8040..=8689 => "B25",
-        8690..=9039 => "B26",
-        9210..=9659 => "B28",
-        9660..=9769 => "B29",
-        9770..=9869 => "B30",
-        9870..=9919 => "B31",
-        9920..=10359 => "B32",
-        36000..=36199 => "B33",
-        36200..=36349 => "B34",
-        36350..=36949 => "B35",
-        36950..=37549 => "B36",
-        37550..=37749 => "B37",
-        37750..=38249 => "B38",
-        38250..=38649 => "B39",
-        38650..=39649 => "B40",
-        _ => "Unknown",
+        None
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Duplex {
+    Fdd,
+    Tdd,
+    /// Supplemental downlink, only used as a carrier aggregation secondary cell
+    Sdl,
+}
+
+impl Duplex {
+    pub fn label(self) -> &'static str {
+        match self {
+            Duplex::Fdd => "FDD",
+            Duplex::Tdd => "TDD",
+            Duplex::Sdl => "SDL",
+        }
+    }
+}
+
+/// Which side of the band an EARFCN was taken from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Link {
+    Downlink,
+    Uplink,
+}
+
+/// What an EARFCN stands for. For TDD both frequencies are the same; `ul_mhz` is
+/// `None` on SDL bands and on downlink channels with no uplink counterpart (B66, B70).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LteChannel {
+    pub band: usize,
+    pub duplex: Duplex,
+    pub link: Link,
+    pub dl_mhz: f64,
+    pub ul_mhz: Option<f64>,
+}
+
+impl LteChannel {
+    /// Duplex mode and centre frequencies, e.g. "FDD, DL 1835.0 MHz, UL 1740.0 MHz"
+    pub fn frequencies(&self) -> String {
+        match (self.duplex, self.ul_mhz) {
+            (Duplex::Fdd, Some(ul_mhz)) => format!("FDD, DL {:.1} MHz, UL {:.1} MHz", self.dl_mhz, ul_mhz),
+            (duplex, _) => format!("{}, {:.1} MHz", duplex.label(), self.dl_mhz),
+        }
     }
 }
 
+impl fmt::Display for LteChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B{} {}", self.band, self.frequencies())
+    }
+}
+
+/// Channel range of one link: lowest frequency in 100 kHz, N_Offs, last channel
+type Raster = (i32, i32, i32);
+
+/// E-UTRA channel numbers, TS 36.101 table 5.7.3-1: band, duplex, downlink and uplink rasters.
+/// TDD bands have a single raster, SDL bands no uplink.
+const LTE_BANDS: &[(usize, Duplex, Raster, Option<Raster>)] = &[
+    (1, Duplex::Fdd, (21100, 0, 599), Some((19200, 18000, 18599))),
+    (2, Duplex::Fdd, (19300, 600, 1199), Some((18500, 18600, 19199))),
+    (3, Duplex::Fdd, (18050, 1200, 1949), Some((17100, 19200, 19949))),
+    (4, Duplex::Fdd, (21100, 1950, 2399), Some((17100, 19950, 20399))),
+    (5, Duplex::Fdd, (8690, 2400, 2649), Some((8240, 20400, 20649))),
+    (6, Duplex::Fdd, (8750, 2650, 2749), Some((8300, 20650, 20749))),
+    (7, Duplex::Fdd, (26200, 2750, 3449), Some((25000, 20750, 21449))),
+    (8, Duplex::Fdd, (9250, 3450, 3799), Some((8800, 21450, 21799))),
+    (9, Duplex::Fdd, (18449, 3800, 4149), Some((17499, 21800, 22149))),
+    (10, Duplex::Fdd, (21100, 4150, 4749), Some((17100, 22150, 22749))),
+    (11, Duplex::Fdd, (14759, 4750, 4949), Some((14279, 22750, 22949))),
+    (12, Duplex::Fdd, (7290, 5010, 5179), Some((6990, 23010, 23179))),
+    (13, Duplex::Fdd, (7460, 5180, 5279), Some((7770, 23180, 23279))),
+    (14, Duplex::Fdd, (7580, 5280, 5379), Some((7880, 23280, 23379))),
+    (17, Duplex::Fdd, (7340, 5730, 5849), Some((7040, 23730, 23849))),
+    (18, Duplex::Fdd, (8600, 5850, 5999), Some((8150, 23850, 23999))),
+    (19, Duplex::Fdd, (8750, 6000, 6149), Some((8300, 24000, 24149))),
+    (20, Duplex::Fdd, (7910, 6150, 6449), Some((8320, 24150, 24449))),
+    (21, Duplex::Fdd, (14959, 6450, 6599), Some((14479, 24450, 24599))),
+    (22, Duplex::Fdd, (35100, 6600, 7399), Some((34100, 24600, 25399))),
+    (23, Duplex::Fdd, (21800, 7500, 7699), Some((20000, 25500, 25699))),
+    (24, Duplex::Fdd, (15250, 7700, 8039), Some((16265, 25700, 26039))),
+    (25, Duplex::Fdd, (19300, 8040, 8689), Some((18500, 26040, 26689))),
+    (26, Duplex::Fdd, (8590, 8690, 9039), Some((8140, 26690, 27039))),
+    (27, Duplex::Fdd, (8520, 9040, 9209), Some((8070, 27040, 27209))),
+    (28, Duplex::Fdd, (7580, 9210, 9659), Some((7030, 27210, 27659))),
+    (29, Duplex::Sdl, (7170, 9660, 9769), None),
+    (30, Duplex::Fdd, (23500, 9770, 9869), Some((23050, 27660, 27759))),
+    (31, Duplex::Fdd, (4625, 9870, 9919), Some((4525, 27760, 27809))),
+    (32, Duplex::Sdl, (14520, 9920, 10359), None),
+    (33, Duplex::Tdd, (19000, 36000, 36199), None),
+    (34, Duplex::Tdd, (20100, 36200, 36349), None),
+    (35, Duplex::Tdd, (18500, 36350, 36949), None),
+    (36, Duplex::Tdd, (19300, 36950, 37549), None),
+    (37, Duplex::Tdd, (19100, 37550, 37749), None),
+    (38, Duplex::Tdd, (25700, 37750, 38249), None),
+    (39, Duplex::Tdd, (18800, 38250, 38649), None),
+    (40, Duplex::Tdd, (23000, 38650, 39649), None),
+    (41, Duplex::Tdd, (24960, 39650, 41589), None),
+    (42, Duplex::Tdd, (34000, 41590, 43589), None),
+    (43, Duplex::Tdd, (36000, 43590, 45589), None),
+    (44, Duplex::Tdd, (7030, 45590, 46589), None),
+    (45, Duplex::Tdd, (14470, 46590, 46789), None),
+    (46, Duplex::Tdd, (51500, 46790, 54539), None),
+    (47, Duplex::Tdd, (58550, 54540, 55239), None),
+    (48, Duplex::Tdd, (35500, 55240, 56739), None),
+    (49, Duplex::Tdd, (35500, 56740, 58239), None),
+    (50, Duplex::Tdd, (14320, 58240, 59089), None),
+    (51, Duplex::Tdd, (14270, 59090, 59139), None),
+    (52, Duplex::Tdd, (33000, 59140, 60139), None),
+    (53, Duplex::Tdd, (24835, 60140, 60254), None),
+    (65, Duplex::Fdd, (21100, 65536, 66435), Some((19200, 131072, 131971))),
+    (66, Duplex::Fdd, (21100, 66436, 67335), Some((17100, 131972, 132671))),
+    (67, Duplex::Sdl, (7380, 67336, 67535), None),
+    (68, Duplex::Fdd, (7530, 67536, 67835), Some((6980, 132672, 132971))),
+    (69, Duplex::Sdl, (25700, 67836, 68335), None),
+    (70, Duplex::Fdd, (19950, 68336, 68585), Some((16950, 132972, 133121))),
+    (71, Duplex::Fdd, (6170, 68586, 68935), Some((6630, 133122, 133471))),
+    (72, Duplex::Fdd, (4610, 68936, 68985), Some((4510, 133472, 133521))),
+    (73, Duplex::Fdd, (4600, 68986, 69035), Some((4500, 133522, 133571))),
+    (74, Duplex::Fdd, (14750, 69036, 69465), Some((14270, 133572, 134001))),
+    (75, Duplex::Sdl, (14320, 69466, 70315), None),
+    (76, Duplex::Sdl, (14270, 70316, 70365), None),
+    (85, Duplex::Fdd, (7280, 70366, 70545), Some((6980, 134002, 134181))),
+    (87, Duplex::Fdd, (4200, 70546, 70595), Some((4100, 134182, 134231))),
+    (88, Duplex::Fdd, (4220, 70596, 70645), Some((4120, 134232, 134281))),
+];
+
+/// Frequency of `earfcn` in a raster, `None` outside of it
+fn raster_frequency(&(low, offset, last): &Raster, earfcn: i32) -> Option<f64> {
+    (offset..=last).contains(&earfcn).then(|| (low + earfcn - offset) as f64 / 10.0)
+}
+
+/// Band, duplex mode and centre frequencies of a downlink or uplink EARFCN (TS 36.101 5.7.3).
+/// The other link is the one at the default duplex distance.
+pub fn lte_channel(earfcn: i32) -> Option<LteChannel> {
+    LTE_BANDS.iter().find_map(|&(band, duplex, dl, ul)| {
+        let (link, dl_mhz, ul_mhz) = match raster_frequency(&dl, earfcn) {
+            Some(dl_mhz) => {
+                let ul_mhz = match ul {
+                    Some(ul) => raster_frequency(&ul, earfcn - dl.1 + ul.1),
+                    None => (duplex == Duplex::Tdd).then_some(dl_mhz),
+                };
+                (Link::Downlink, dl_mhz, ul_mhz)
+            }
+            None => {
+                let ul = ul?;
+                let ul_mhz = raster_frequency(&ul, earfcn)?;
+                (Link::Uplink, raster_frequency(&dl, earfcn - ul.1 + dl.1)?, Some(ul_mhz))
+            }
+        };
+        Some(LteChannel { band, duplex, link, dl_mhz, ul_mhz })
+    })
+}
+
+/// "B3" style label, "Unknown" for a channel outside every band
+pub fn get_band_lte(earfcn: i32) -> String {
+    lte_channel(earfcn).map_or("Unknown".to_string(), |channel| format!("B{}", channel.band))
+}
+
+/// What a UARFCN stands for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UmtsChannel {
+    pub band: usize,
+    pub link: Link,
+    pub frequency_mhz: f64,
+}
+
+impl fmt::Display for UmtsChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let link = match self.link {
+            Link::Downlink => "DL",
+            Link::Uplink => "UL",
+        };
+        write!(f, "B{} {} {:.1} MHz", self.band, link, self.frequency_mhz)
+    }
+}
+
+/// Channel range of one link: F_Offset in 100 kHz, first and last channel
+type UmtsRaster = (i32, i32, i32);
+
+/// UTRA FDD channel numbers, TS 25.101 tables 5.1 and 5.2: band, downlink and uplink
+/// rasters. F = F_Offset + UARFCN / 5.
+const UMTS_BANDS: &[(usize, UmtsRaster, UmtsRaster)] = &[
+    (1, (0, 10562, 10838), (0, 9612, 9888)),
+    (2, (0, 9662, 9938), (0, 9262, 9538)),
+    (3, (15750, 1162, 1513), (15250, 937, 1288)),
+    (4, (18050, 1537, 1738), (14500, 1312, 1513)),
+    (5, (0, 4357, 4458), (0, 4132, 4233)),
+    (6, (0, 4387, 4413), (0, 4162, 4188)),
+    (7, (21750, 2237, 2563), (21000, 2012, 2338)),
+    (8, (3400, 2937, 3088), (3400, 2712, 2863)),
+    (9, (0, 9237, 9387), (0, 8762, 8912)),
+    (10, (14900, 3112, 3388), (11350, 2887, 3163)),
+    (11, (7360, 3712, 3787), (7330, 3487, 3562)),
+    (12, (-370, 3842, 3903), (-220, 3617, 3678)),
+    (13, (-550, 4017, 4043), (210, 3792, 3818)),
+    (14, (-630, 4117, 4143), (120, 3892, 3918)),
+    (19, (7350, 712, 763), (7700, 312, 363)),
+    (20, (-1090, 4512, 4638), (-230, 4287, 4413)),
+    (21, (13260, 862, 912), (13580, 462, 512)),
+    (22, (25800, 4662, 5038), (25250, 4437, 4813)),
+    (25, (9100, 5112, 5413), (8750, 4887, 5188)),
+    (26, (-2910, 5762, 5913), (-2910, 5537, 5688)),
+];
+
+/// Additional channels on the 100 kHz shifted raster, TS 25.101 table 5.2:
+/// band, link, offset in 100 kHz and the channels
+const UMTS_ADDITIONAL_CHANNELS: &[(usize, Link, i32, &[i32])] = &[
+    (2, Link::Downlink, 18501, &[412, 437, 462, 487, 512, 537, 562, 587, 612, 637, 662, 687]),
+    (2, Link::Uplink, 18501, &[12, 37, 62, 87, 112, 137, 162, 187, 212, 237, 262, 287]),
+    (4, Link::Downlink, 17351, &[1887, 1912, 1937, 1962, 1987, 2012, 2037, 2062, 2087]),
+    (4, Link::Uplink, 13801, &[1662, 1687, 1712, 1737, 1762, 1787, 1812, 1837, 1862]),
+    (5, Link::Downlink, 6701, &[1007, 1012, 1032, 1037, 1062, 1087]),
+    (5, Link::Uplink, 6701, &[782, 787, 807, 812, 837, 862]),
+    (6, Link::Downlink, 6701, &[1037, 1062]),
+    (6, Link::Uplink, 6701, &[812, 837]),
+    (7, Link::Downlink, 21051, &[2587, 2612, 2637, 2662, 2687, 2712, 2737, 2762, 2787, 2812, 2837, 2862, 2887, 2912]),
+    (7, Link::Uplink, 20301, &[2362, 2387, 2412, 2437, 2462, 2487, 2512, 2537, 2562, 2587, 2612, 2637, 2662, 2687]),
+    (10, Link::Downlink, 14301, &[3412, 3437, 3462, 3487, 3512, 3537, 3562, 3587, 3612, 3637, 3662, 3687]),
+    (10, Link::Uplink, 10751, &[3187, 3212, 3237, 3262, 3287, 3312, 3337, 3362, 3387, 3412, 3437, 3462]),
+    (12, Link::Downlink, -549, &[3932, 3957, 3962, 3987, 3992]),
+    (12, Link::Uplink, -399, &[3707, 3732, 3737, 3762, 3767]),
+    (13, Link::Downlink, -649, &[4067, 4092]),
+    (13, Link::Uplink, 111, &[3842, 3867]),
+    (14, Link::Downlink, -729, &[4167, 4192]),
+    (14, Link::Uplink, 21, &[3942, 3967]),
+    (19, Link::Downlink, 7201, &[787, 812, 837]),
+    (19, Link::Uplink, 7551, &[387, 412, 437]),
+    (25, Link::Downlink, 6741, &[6292, 6317, 6342, 6367, 6392, 6417, 6442, 6467, 6492, 6517, 6542, 6567, 6592]),
+    (25, Link::Uplink, 6391, &[6067, 6092, 6117, 6142, 6167, 6192, 6217, 6242, 6267, 6292, 6317, 6342, 6367]),
+    (26, Link::Downlink, -2909, &[5767, 5772, 5792, 5797, 5817, 5822, 5842, 5847, 5867, 5872, 5892]),
+    (26, Link::Uplink, -2909, &[5542, 5547, 5567, 5572, 5592, 5597, 5617, 5622, 5642, 5647, 5667]),
+];
+
+/// Band and frequency of a UARFCN (TS 25.101 5.4.4). Downlink and uplink numbers
+/// of different bands overlap; modems report the downlink one, so it is tried first.
+/// Band XXVI numbers its additional channels inside the general range, the listed
+/// channel is the exact one.
+pub fn umts_channel(uarfcn: i32) -> Option<UmtsChannel> {
+    let channel = |band: usize, link: Link, offset: i32| UmtsChannel { band, link, frequency_mhz: (offset + 2 * uarfcn) as f64 / 10.0 };
+    let general = |link: Link| UMTS_BANDS.iter().find_map(|&(band, dl, ul)| {
+        let (offset, first, last) = if link == Link::Downlink { dl } else { ul };
+        (first..=last).contains(&uarfcn).then(|| channel(band, link, offset))
+    });
+    let additional = |link: Link| UMTS_ADDITIONAL_CHANNELS.iter()
+        .find(|&&(_, channel_link, _, channels)| channel_link == link && channels.contains(&uarfcn))
+        .map(|&(band, _, offset, _)| channel(band, link, offset));
+
+    additional(Link::Downlink)
+        .or_else(|| general(Link::Downlink))
+        .or_else(|| additional(Link::Uplink))
+        .or_else(|| general(Link::Uplink))
+}
+
+/// "B1" style label, "Unknown" for a channel outside every band
+pub fn get_band_umts(uarfcn: i32) -> String {
+    umts_channel(uarfcn).map_or("Unknown".to_string(), |channel| format!("B{}", channel.band))
+}
+
+/// NR band downlink ranges in MHz (TS 38.101-1/-2). Many bands overlap, e.g. n78
+/// lies inside n77, so a frequency alone does not always tell the band.
+const NR_BANDS: &[(usize, Duplex, f64, f64)] = &[
+    (1, Duplex::Fdd, 2110.0, 2170.0),
+    (2, Duplex::Fdd, 1930.0, 1990.0),
+    (3, Duplex::Fdd, 1805.0, 1880.0),
+    (5, Duplex::Fdd, 869.0, 894.0),
+    (7, Duplex::Fdd, 2620.0, 2690.0),
+    (8, Duplex::Fdd, 925.0, 960.0),
+    (12, Duplex::Fdd, 729.0, 746.0),
+    (13, Duplex::Fdd, 746.0, 756.0),
+    (14, Duplex::Fdd, 758.0, 768.0),
+    (18, Duplex::Fdd, 860.0, 875.0),
+    (20, Duplex::Fdd, 791.0, 821.0),
+    (25, Duplex::Fdd, 1930.0, 1995.0),
+    (26, Duplex::Fdd, 859.0, 894.0),
+    (28, Duplex::Fdd, 758.0, 803.0),
+    (29, Duplex::Sdl, 717.0, 728.0),
+    (30, Duplex::Fdd, 2350.0, 2360.0),
+    (34, Duplex::Tdd, 2010.0, 2025.0),
+    (38, Duplex::Tdd, 2570.0, 2620.0),
+    (39, Duplex::Tdd, 1880.0, 1920.0),
+    (40, Duplex::Tdd, 2300.0, 2400.0),
+    (41, Duplex::Tdd, 2496.0, 2690.0),
+    (48, Duplex::Tdd, 3550.0, 3700.0),
+    (66, Duplex::Fdd, 2110.0, 2200.0),
+    (70, Duplex::Fdd, 1995.0, 2020.0),
+    (71, Duplex::Fdd, 617.0, 652.0),
+    (75, Duplex::Sdl, 1432.0, 1517.0),
+    (76, Duplex::Sdl, 1427.0, 1432.0),
+    (77, Duplex::Tdd, 3300.0, 4200.0),
+    (78, Duplex::Tdd, 3300.0, 3800.0),
+    (79, Duplex::Tdd, 4400.0, 5000.0),
+    (257, Duplex::Tdd, 26500.0, 29500.0),
+    (258, Duplex::Tdd, 24250.0, 27500.0),
+    (260, Duplex::Tdd, 37000.0, 40000.0),
+    (261, Duplex::Tdd, 27500.0, 28350.0),
+];
+
+/// What an NR-ARFCN stands for. `bands` holds every band the frequency may belong to,
+/// it is empty for a valid frequency outside the known bands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NrChannel {
+    pub bands: Vec<usize>,
+    /// `None` when the candidate bands do not share one
+    pub duplex: Option<Duplex>,
+    pub frequency_mhz: f64,
+    /// Global frequency raster granularity ΔF_Global
+    pub raster_khz: u32,
+}
+
+impl NrChannel {
+    /// "n78", or the candidates when the band is ambiguous, e.g. "n7/n41"
+    pub fn band_label(&self) -> Option<String> {
+        let bands: Vec<String> = self.bands.iter().map(|band| format!("n{}", band)).collect();
+        (!bands.is_empty()).then(|| bands.join("/"))
+    }
+
+    /// Duplex mode and frequency, e.g. "TDD, 3408.96 MHz"
+    pub fn frequencies(&self) -> String {
+        match self.duplex {
+            Some(duplex) => format!("{}, {} MHz", duplex.label(), self.frequency_mhz),
+            None => format!("{} MHz", self.frequency_mhz),
+        }
+    }
+}
+
+impl fmt::Display for NrChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.band_label() {
+            Some(bands) => write!(f, "{} {}", bands, self.frequencies()),
+            None => write!(f, "{}", self.frequencies()),
+        }
+    }
+}
+
+/// Global frequency raster (TS 38.104 table 5.4.2.1-1): range start in MHz, ΔF_Global in kHz, first N_REF
+const NR_GLOBAL_RASTER: [(f64, u32, i32); 3] = [
+    (0.0, 5, 0),
+    (3000.0, 15, 600_000),
+    (24250.08, 60, 2_016_667),
+];
+
+/// Frequency, raster step and band of an NR-ARFCN. F_REF = F_REF-Offs + ΔF_Global (N_REF – N_REF-Offs),
+/// rounded to the raster so that floating point noise does not show.
+/// `reported_band` is the band the modem gave for the cell: it is taken when it covers the
+/// frequency, otherwise every band that does is listed.
+pub fn nr_channel(nr_arfcn: i32, reported_band: Option<usize>) -> Option<NrChannel> {
+    if !(0..=3_279_165).contains(&nr_arfcn) {
+        return None;
+    }
+    let &(offset_mhz, raster_khz, first) = NR_GLOBAL_RASTER.iter().rev().find(|&&(_, _, first)| nr_arfcn >= first)?;
+    let frequency_mhz = ((offset_mhz * 1000.0 + (nr_arfcn - first) as f64 * raster_khz as f64).round()) / 1000.0;
+    let mut candidates: Vec<(usize, Duplex)> = NR_BANDS.iter()
+        .filter(|&&(_, _, low, high)| (low..=high).contains(&frequency_mhz))
+        .map(|&(band, duplex, ..)| (band, duplex))
+        .collect();
+    if let Some(reported) = candidates.iter().copied().find(|&(band, _)| Some(band) == reported_band) {
+        candidates = vec![reported];
+    }
+    let duplex = candidates.first().map(|&(_, duplex)| duplex)
+        .filter(|&duplex| candidates.iter().all(|&(_, other)| other == duplex));
+    Some(NrChannel {
+        bands: candidates.iter().map(|&(band, _)| band).collect(),
+        duplex,
+        frequency_mhz,
+        raster_khz,
+    })
+}
+
+/// The band of an NR-ARFCN, `None` if it lies in several bands or in none
+pub fn get_band_nr(nr_arfcn: i32) -> Option<usize> {
+    match nr_channel(nr_arfcn, None)?.bands[..] {
+        [band] => Some(band),
+        _ => None,
+    }
+}
+
+/// Length of the gNB ID in the NR cell identity, chosen by each operator (TS 38.413 clause 9.3.1.6)
+pub const GNB_ID_BITS: RangeInclusive<u32> = 22..=32;
+
+/// Most networks use 24 bits; cell databases need the length the operator actually uses
+pub const DEFAULT_GNB_ID_BITS: u32 = 24;
+
+/// eNB ID and local cell ID of a 28 bit E-UTRAN cell identity: the eNB ID is the
+/// leftmost 20 bits (TS 36.413 clause 9.2.1.38). `None` for an unknown cell.
+pub fn split_eci(eci: i32) -> Option<(u32, u32)> {
+    let eci = u32::try_from(eci).ok().filter(|&eci| eci > 0 && eci < 1 << 28)?;
+    Some((eci >> 8, eci & 0xFF))
+}
+
+/// gNB ID and local cell ID of a 36 bit NR cell identity, the gNB ID is the leftmost `gnb_id_bits`
+pub fn split_nci(nci: i64, gnb_id_bits: u32) -> Option<(u32, u32)> {
+    let nci = u64::try_from(nci).ok().filter(|&nci| nci > 0 && nci < 1 << 36)?;
+    if !GNB_ID_BITS.contains(&gnb_id_bits) {
+        return None;
+    }
+    let cell_id_bits = 36 - gnb_id_bits;
+    Some(((nci >> cell_id_bits) as u32, (nci & ((1 << cell_id_bits) - 1)) as u32))
+}
+
 pub fn hex_to_decimal(hex: &str) -> Result<i32, std::num::ParseIntError> {
     i32::from_str_radix(hex.trim_start_matches("0x"), 16)
 }
 
-// pub fn calculate_distance(timing_advance: &str) -> String {
-//     let computed_value = (timing_advance * 78.125) / 1000.0;
-//     let rounded_value = (computed_value * 1000.0).round() / 1000.0;
-//
-//     // Используем `Option::or` для выбора между значением и None
-//     let distance = Some(rounded_value).or(None);
-// }
\ No newline at end of file
+/// Timing advance value the XMM modules report for cells the modem is not synchronized to
+const TIMING_ADVANCE_NOT_AVAILABLE: [u32; 2] = [0x7FFFFFFF, 0xFFFFFFFF];
+
+/// Timing advance in the `"0x00000003"` form of `+XMCI`, `None` for the "not available" values
+pub fn parse_timing_advance(hex: &str) -> Option<u32> {
+    u32::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+        .filter(|timing_advance| !TIMING_ADVANCE_NOT_AVAILABLE.contains(timing_advance))
+}
+
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// LTE basic time unit Ts, 1 / (15 kHz * 2048)
+const TS: f64 = 1.0 / (15_000.0 * 2048.0);
+
+/// GSM bit period, 48/13 µs
+const GSM_BIT_PERIOD: f64 = 48.0e-6 / 13.0;
+
+/// Timing advance formulas differ in step and range per access technology.
+/// UMTS has no timing advance, the distance of a 3G cell is unknown.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimingAdvanceRat {
+    /// T_A in bit periods, 0..=63 (TS 45.010 clause 5.6.2)
+    Gsm,
+    /// T_A in steps of 16 Ts, 0..=1282 (TS 36.213 clause 4.2.3)
+    Lte,
+}
+
+impl TimingAdvanceRat {
+    /// Largest timing advance and the round trip time of one step
+    fn step(self) -> (u32, f64) {
+        match self {
+            TimingAdvanceRat::Gsm => (63, GSM_BIT_PERIOD),
+            TimingAdvanceRat::Lte => (1282, 16.0 * TS),
+        }
+    }
+}
+
+/// Distance to the cell with the range the timing advance granularity leaves:
+/// the network rounds the round trip time to a whole step.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+pub struct DistanceEstimate {
+    #[serde(rename = "distance_m")]
+    pub distance: f64,
+    #[serde(rename = "min_m")]
+    pub min: f64,
+    #[serde(rename = "max_m")]
+    pub max: f64,
+}
+
+impl fmt::Display for DistanceEstimate {
+    /// `234m (195-273m)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}m ({}-{}m)", self.distance, self.min, self.max)
+    }
+}
+
+pub fn timing_advance_distance(rat: TimingAdvanceRat, timing_advance: u32) -> Option<DistanceEstimate> {
+    let (max_timing_advance, step) = rat.step();
+    if timing_advance > max_timing_advance {
+        return None;
+    }
+    // A step is round trip time, the distance is half of it: 553.5 m in GSM, 78.07 m in LTE
+    let step_m = step * SPEED_OF_LIGHT / 2.0;
+    let timing_advance = f64::from(timing_advance);
+    Some(DistanceEstimate {
+        distance: (timing_advance * step_m).round(),
+        min: ((timing_advance - 0.5).max(0.0) * step_m).round(),
+        max: ((timing_advance + 0.5) * step_m).round(),
+    })
+}
@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use crate::modem_tools::parse_report::ParseReport;
+use crate::modem_tools::registration::NetworkRegistration;
+use crate::modem_tools::types::{CarrierInfo, ModemInfo};
+
+/// Output format of the non-interactive commands.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Text,
+    Json,
+    Toml,
+}
+
+impl ExportFormat {
+    /// Serializes `value`, `None` for the human-readable text format.
+    pub fn serialize<T: Serialize>(self, value: &T) -> Option<Result<String, String>> {
+        match self {
+            ExportFormat::Text => None,
+            ExportFormat::Json => Some(serde_json::to_string_pretty(value).map_err(|err| err.to_string())),
+            ExportFormat::Toml => Some(toml::to_string(value).map_err(|err| err.to_string())),
+        }
+    }
+}
+
+/// Signal part of `ModemInfo`, same field names as in the full export.
+/// Values the modem did not report are left out of TOML and `null` in JSON.
+#[derive(Serialize)]
+pub struct SignalReport<'a> {
+    pub registration: &'a NetworkRegistration,
+    pub csq: Option<i32>,
+    pub csq_percent: Option<i32>,
+    pub rssi_dbm: Option<i32>,
+    pub rsrp_dbm: Option<i32>,
+    pub rsrq_db: Option<i32>,
+    pub sinr_db: Option<i32>,
+    pub rxlev_dbm: Option<i32>,
+    pub ber: Option<i32>,
+    pub rscp_dbm: Option<i32>,
+    pub ecio_db: Option<i32>,
+    pub distance_m: Option<f64>,
+    pub carriers: &'a [CarrierInfo],
+    pub parse_report: &'a ParseReport,
+}
+
+impl<'a> From<&'a ModemInfo> for SignalReport<'a> {
+    fn from(info: &'a ModemInfo) -> SignalReport<'a> {
+        SignalReport {
+            registration: &info.registration,
+            csq: info.csq,
+            csq_percent: info.csq_perc,
+            rssi_dbm: info.rssi,
+            rsrp_dbm: info.rsrp,
+            rsrq_db: info.rsrq,
+            sinr_db: info.sinr,
+            rxlev_dbm: info.rxlev,
+            ber: info.ber,
+            rscp_dbm: info.rscp,
+            ecio_db: info.ecio,
+            distance_m: info.distance,
+            carriers: &info.carriers,
+            parse_report: &info.parse_report,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BandsReport<'a> {
+    pub umts: &'a [usize],
+    pub lte: &'a [usize],
+    pub nr: &'a [usize],
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use crate::modem_tools::converters::{get_band_lte, nr_channel, umts_channel};
+use crate::modem_tools::types::{ModemInfo, NrMode};
+
+/// Three hours of samples at the default 3 s poll interval
+pub const DEFAULT_HISTORY_CAPACITY: usize = 3600;
+
+const CSV_HEADER: &str = "timestamp_ms,rsrp_dbm,rsrq_db,sinr_db,rssi_dbm,csq,band,pci,earfcn,ca,registration";
+
+/// One poll result. The serving cell is the first LTE carrier, or the NR one in SA mode;
+/// on UMTS there is only the band. A measurement the modem did not report is an empty
+/// CSV field and `null` in JSON Lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistorySample {
+    pub timestamp_ms: u64,
+    pub rsrp_dbm: Option<i32>,
+    pub rsrq_db: Option<i32>,
+    pub sinr_db: Option<i32>,
+    pub rssi_dbm: Option<i32>,
+    pub csq: Option<i32>,
+    pub band: String,
+    pub pci: Option<i32>,
+    /// Of the LTE serving cell only
+    pub earfcn: Option<i32>,
+    /// Carrier aggregation configuration, e.g. `B3@20MHz B7@10MHz`
+    pub ca: String,
+    /// Registration status of the domain in use, e.g. `home` or `denied`; empty when not reported
+    pub registration: String,
+}
+
+impl HistorySample {
+    pub fn new(info: &ModemInfo, timestamp_ms: u64) -> HistorySample {
+        let mut sample = HistorySample {
+            timestamp_ms,
+            rsrp_dbm: info.rsrp,
+            rsrq_db: info.rsrq,
+            sinr_db: info.sinr,
+            rssi_dbm: info.rssi,
+            csq: info.csq,
+            band: String::new(),
+            pci: None,
+            earfcn: None,
+            ca: info.band.as_deref().unwrap_or_default().trim().to_string(),
+            registration: info.registration.current()
+                .map_or(String::new(), |(_, registration)| registration.status.key().to_string()),
+        };
+
+        let nr_serving = info.nr_carriers.first().filter(|_| info.nr_mode == NrMode::Sa);
+        if let Some(carrier) = nr_serving {
+            // Only the NR cell is measured in SA mode
+            sample.rsrp_dbm = Some(carrier.ss_rsrp);
+            sample.rsrq_db = Some(carrier.ss_rsrq);
+            sample.sinr_db = Some(carrier.ss_sinr);
+            sample.band = nr_channel(carrier.nr_arfcn, carrier.band)
+                .and_then(|channel| channel.band_label())
+                .unwrap_or_default();
+            sample.pci = Some(carrier.pci);
+        } else if let Some(carrier) = info.carriers.first() {
+            sample.band = get_band_lte(carrier.earfcn);
+            sample.pci = Some(carrier.pci);
+            sample.earfcn = Some(carrier.earfcn);
+        } else if let Some(uarfcn) = info.dluarfnc.filter(|_| info.is_umts()) {
+            sample.band = umts_channel(uarfcn).map_or(String::new(), |channel| format!("B{}", channel.band));
+        }
+        sample
+    }
+
+    fn csv_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.timestamp_ms, optional_field(self.rsrp_dbm), optional_field(self.rsrq_db),
+            optional_field(self.sinr_db), optional_field(self.rssi_dbm), optional_field(self.csq),
+            csv_field(&self.band), optional_field(self.pci), optional_field(self.earfcn), csv_field(&self.ca), self.registration
+        )
+    }
+}
+
+/// Serving cell or band change between two consecutive samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellChange<'a> {
+    pub from: &'a HistorySample,
+    pub to: &'a HistorySample,
+}
+
+impl CellChange<'_> {
+    pub fn describe(&self) -> String {
+        let mut changes = Vec::new();
+        if self.from.pci != self.to.pci {
+            changes.push(format!("PCI {}→{}", change_label(self.from.pci), change_label(self.to.pci)));
+        }
+        if self.from.earfcn != self.to.earfcn {
+            changes.push(format!("EARFCN {}→{}", change_label(self.from.earfcn), change_label(self.to.earfcn)));
+        }
+        if self.from.band != self.to.band {
+            changes.push(format!("{}→{}", self.from.band, self.to.band));
+        }
+        changes.join(" ")
+    }
+}
+
+/// Points where the serving cell (PCI/EARFCN) or the band changed.
+pub fn cell_changes<'a>(samples: &[&'a HistorySample]) -> Vec<CellChange<'a>> {
+    samples.windows(2)
+        .filter(|pair| pair[0].pci != pair[1].pci || pair[0].earfcn != pair[1].earfcn || pair[0].band != pair[1].band)
+        .map(|pair| CellChange { from: pair[0], to: pair[1] })
+        .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn optional_field(value: Option<i32>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+fn change_label(value: Option<i32>) -> String {
+    value.map_or("--".to_string(), |value| value.to_string())
+}
+
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryFormat {
+    Csv,
+    Jsonl,
+}
+
+impl HistoryFormat {
+    /// `.jsonl` and `.json` files get JSON Lines, anything else CSV.
+    pub fn from_path(path: &str) -> HistoryFormat {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("json") => HistoryFormat::Jsonl,
+            _ => HistoryFormat::Csv,
+        }
+    }
+}
+
+/// File every sample is appended to as soon as it is recorded.
+pub struct HistoryWriter {
+    file: Box<dyn Write + Send>,
+    format: HistoryFormat,
+}
+
+impl HistoryWriter {
+    /// Appends to `path`, so a restarted drive test continues the same file.
+    /// The CSV header is only written to a new or empty file.
+    pub fn create(path: &str) -> Result<HistoryWriter, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let header = file.metadata()?.len() == 0;
+        HistoryWriter::new(Box::new(BufWriter::new(file)), HistoryFormat::from_path(path), header)
+    }
+
+    pub fn new(mut file: Box<dyn Write + Send>, format: HistoryFormat, header: bool) -> Result<HistoryWriter, io::Error> {
+        if header && format == HistoryFormat::Csv {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+        Ok(HistoryWriter { file, format })
+    }
+
+    pub fn write(&mut self, sample: &HistorySample) -> Result<(), io::Error> {
+        match self.format {
+            HistoryFormat::Csv => writeln!(self.file, "{}", sample.csv_line())?,
+            HistoryFormat::Jsonl => {
+                serde_json::to_writer(&mut self.file, sample)?;
+                writeln!(self.file)?;
+            }
+        }
+        // A drive test usually ends with the laptop lid closing, keep what we have
+        self.file.flush()
+    }
+}
+
+/// The last `capacity` poll results, oldest first.
+pub struct SignalHistory {
+    samples: VecDeque<HistorySample>,
+    capacity: usize,
+    writer: Option<HistoryWriter>,
+}
+
+impl Default for SignalHistory {
+    fn default() -> Self {
+        SignalHistory::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl SignalHistory {
+    pub fn with_capacity(capacity: usize) -> SignalHistory {
+        SignalHistory { samples: VecDeque::with_capacity(capacity), capacity: capacity.max(1), writer: None }
+    }
+
+    /// Streams every following sample to `writer` as well.
+    pub fn stream_to(&mut self, writer: HistoryWriter) {
+        self.writer = Some(writer);
+    }
+
+    pub fn record(&mut self, info: &ModemInfo) -> Result<(), io::Error> {
+        self.push(HistorySample::new(info, timestamp()))
+    }
+
+    pub fn push(&mut self, sample: HistorySample) -> Result<(), io::Error> {
+        let written = match self.writer.as_mut() {
+            Some(writer) => writer.write(&sample),
+            None => Ok(()),
+        };
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        written
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &HistorySample> {
+        self.samples.iter()
+    }
+
+    /// Writes the buffered samples to `path`, returns how many were written.
+    pub fn save(&self, path: &str) -> Result<usize, io::Error> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut writer = HistoryWriter::new(Box::new(file), HistoryFormat::from_path(path), true)?;
+        for sample in &self.samples {
+            writer.write(sample)?;
+        }
+        Ok(self.samples.len())
+    }
+}
@@ -1,4 +1,15 @@
+pub mod at_response;
 pub mod converters;
+pub mod export;
+pub mod history;
 pub mod modem;
+pub mod parse_report;
+pub mod parsers;
+pub mod plmn;
+pub mod recording;
+pub mod registration;
+pub mod session;
+pub mod simulator;
 pub mod types;
-pub mod supported_modems;
\ No newline at end of file
+pub mod supported_modems;
+pub mod urc;
@@ -0,0 +1,122 @@
+use std::fmt;
+use serde::Serialize;
+use crate::modem_tools::at_response::{AtError, FinalResult};
+
+/// Why a response gave nothing usable
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum ParseProblem {
+    /// The command was sent but its response is not in the poll output
+    Missing,
+    /// The response is there in an unexpected format
+    Malformed { line: String },
+    /// The modem answered with ERROR, +CME ERROR...
+    Error { result: String },
+    /// The modem did not answer in time
+    Timeout,
+    /// The command could not be sent or its answer read
+    Io { error: String },
+}
+
+/// `command` is the response prefix (`+CSQ`), or the command line for an error
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseIssue {
+    pub command: String,
+    #[serde(flatten)]
+    pub problem: ParseProblem,
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.problem {
+            ParseProblem::Missing => write!(f, "{} missing", self.command),
+            ParseProblem::Malformed { .. } => write!(f, "{} malformed", self.command),
+            ParseProblem::Error { result } => write!(f, "{} {}", self.command, result),
+            ParseProblem::Timeout => write!(f, "{} timed out", self.command),
+            ParseProblem::Io { error } => write!(f, "{} {}", self.command, error),
+        }
+    }
+}
+
+/// Which responses of a poll could not be used. Every field is parsed on its own,
+/// so the rest of the poll is still shown.
+///
+/// A response only counts as missing when the poll output has the echo of a
+/// command that should have produced it: each parser reads what it finds and
+/// profiles do not poll the same commands.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ParseReport {
+    pub issues: Vec<ParseIssue>,
+    #[serde(skip)]
+    sent: Vec<String>,
+    #[serde(skip)]
+    failed: Vec<String>,
+}
+
+impl ParseReport {
+    /// Records the commands echoed in `info_string` and the ones that failed
+    pub fn new(info_string: &str) -> ParseReport {
+        let mut report = ParseReport::default();
+        let mut command: Option<&str> = None;
+        for line in info_string.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.to_ascii_uppercase().starts_with("AT") && !line.contains(':') {
+                report.sent.push(line.to_ascii_uppercase());
+                command = Some(line);
+                continue;
+            }
+            let Some(result) = FinalResult::parse(line) else { continue };
+            if let (false, Some(command)) = (result.is_ok(), command) {
+                let result = match result.description() {
+                    Some(description) => format!("{} ({})", result, description),
+                    None => result.to_string(),
+                };
+                report.failed.push(command.to_ascii_uppercase());
+                report.issues.push(ParseIssue { command: command.to_string(), problem: ParseProblem::Error { result } });
+            }
+            command = None;
+        }
+        report
+    }
+
+    /// Forgets the issues of `prefix`, for a profile that reads a response the shared parser could not
+    pub fn resolve(&mut self, prefix: &str) {
+        self.issues.retain(|issue| issue.command != prefix);
+    }
+
+    /// Issues of the commands and responses carrying one of `prefixes`
+    pub fn issues_for<'a>(&'a self, prefixes: &'a [&str]) -> impl Iterator<Item = &'a ParseIssue> {
+        self.issues.iter().filter(move |issue| prefixes.iter().any(|prefix| issue.command.to_ascii_uppercase().contains(prefix)))
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The `prefix` response is not in the poll output, an issue if its command was sent
+    pub fn missing(&mut self, prefix: &str) {
+        let sent = self.sent.iter().any(|command| command.contains(prefix));
+        let failed = self.failed.iter().any(|command| command.contains(prefix));
+        if sent && !failed {
+            self.add(prefix, ParseProblem::Missing);
+        }
+    }
+
+    /// `command` got no answer at all, nothing of it is in the poll output
+    pub fn unanswered(&mut self, command: &str, err: &AtError) {
+        let problem = match err {
+            AtError::Timeout(_) => ParseProblem::Timeout,
+            err => ParseProblem::Io { error: err.to_string() },
+        };
+        self.add(command, problem);
+    }
+
+    pub fn malformed(&mut self, prefix: &str, line: String) {
+        self.add(prefix, ParseProblem::Malformed { line });
+    }
+
+    fn add(&mut self, prefix: &str, problem: ParseProblem) {
+        if !self.issues.iter().any(|issue| issue.command == prefix) {
+            self.issues.push(ParseIssue { command: prefix.to_string(), problem });
+        }
+    }
+}
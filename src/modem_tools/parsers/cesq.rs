@@ -0,0 +1,32 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::ModemInfo;
+
+// <rxlev>,<ber>,<rscp>,<ecno>,<rsrq>,<rsrp>
+pub static CESQ_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CESQ: (?P<rxlev>\d+),(?P<ber>\d+),(?P<rscp>\d+),(?P<ecno>\d+),(?P<rsrq>\d+),(?P<rsrp>\d+)"#).unwrap());
+
+/// Index 255 means "not known or not detectable" in `+CESQ`, 99 for the GSM values
+fn cesq_index(value: &str, unknown: i32) -> Option<i32> {
+    value.parse::<i32>().ok().filter(|&index| index != unknown)
+}
+
+/// Extended signal quality, 3GPP TS 27.007 clause 8.69.
+/// Only the values of the access technology in use are reported, the others are unknown.
+pub struct CesqParser;
+
+impl ResponseParser for CesqParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&CESQ_REGEX, lines)?;
+        // RXLEV -111 + index dBm, BER as RXQUAL 0-7
+        info.rxlev = cesq_index(&caps["rxlev"], 99).map(|rxlev| rxlev - 111);
+        info.ber = cesq_index(&caps["ber"], 99);
+        // RSCP -121 + index dBm, Ec/No -24 + index / 2 dB
+        info.rscp = cesq_index(&caps["rscp"], 255).map(|rscp| rscp - 121);
+        info.ecio = cesq_index(&caps["ecno"], 255).map(|ecno| ecno / 2 - 24);
+        // RSRP -141 + index dBm, RSRQ -20 + index / 2 dB
+        info.rsrp = cesq_index(&caps["rsrp"], 255).map(|rsrp| rsrp - 141);
+        info.rsrq = cesq_index(&caps["rsrq"], 255).map(|rsrq| rsrq / 2 - 20);
+        Ok(())
+    }
+}
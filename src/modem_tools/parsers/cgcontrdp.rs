@@ -0,0 +1,22 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::ModemInfo;
+
+pub static CGCONTRDP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGCONTRDP: (?P<index>\d),(?P<cid>\d+),"(?P<apn>[^"]+)","(?P<ip_addr>\d+\.\d+\.\d+\.\d+)\.(?P<mask>\d+\.\d+\.\d+\.\d+)","(?P<dns_prim>[^"]+)","(?P<dns_sec>[^"]+)","(?P<gw_addr>[^"]+)","(?P<p_cscf_prim>[^"]*)","(?P<p_cscf_sec>[^"]*)",(?P<mtu>\d+)"#).unwrap());
+
+/// APN, address, mask, gateway and DNS servers of the first context
+pub struct CgcontrdpParser;
+
+impl ResponseParser for CgcontrdpParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&CGCONTRDP_REGEX, lines)?;
+        info.apn = Some(caps["apn"].to_string());
+        info.ip = Some(caps["ip_addr"].to_string());
+        info.mask = Some(caps["mask"].to_string());
+        info.gw = Some(caps["gw_addr"].to_string());
+        info.dns_prim = Some(caps["dns_prim"].to_string());
+        info.dns_sec = Some(caps["dns_sec"].to_string());
+        Ok(())
+    }
+}
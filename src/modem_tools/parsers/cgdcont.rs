@@ -0,0 +1,33 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::ModemInfo;
+
+// <cid>,<PDP type>,<APN>...
+pub static CGDCONT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGDCONT: (?P<cid>\d+),"(?P<type>[^"]*)","(?P<apn>[^"]*)""#).unwrap());
+// <cid>[,<address 1>[,<address 2>]]
+pub static CGPADDR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGPADDR: (?P<cid>\d+),"?(?P<address>[0-9A-Fa-f.:]+)"?"#).unwrap());
+
+/// Defined PDP contexts, the APN of the first one
+pub struct CgdcontParser;
+
+impl ResponseParser for CgdcontParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&CGDCONT_REGEX, lines)?;
+        info.apn = Some(caps["apn"].to_string()).filter(|apn| !apn.is_empty());
+        Ok(())
+    }
+}
+
+/// Addresses of the defined contexts, the first context that has one gives the IP address.
+/// Contexts without an address answer `+CGPADDR: <cid>` or with an empty string.
+pub struct CgpaddrParser;
+
+impl ResponseParser for CgpaddrParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        if let Some(caps) = lines.iter().find_map(|line| CGPADDR_REGEX.captures(line)) {
+            info.ip = Some(caps["address"].to_string());
+        }
+        Ok(())
+    }
+}
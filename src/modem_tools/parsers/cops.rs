@@ -0,0 +1,50 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::plmn::Plmn;
+use crate::modem_tools::types::{ModemInfo, NrMode};
+
+pub static COPS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?m)\+COPS: (\d)(?:,(\d),"([^"]*)"(?:,(\d+))?)?\r?$"#).unwrap());
+
+/// Operator and access technology
+pub struct CopsParser;
+
+impl ResponseParser for CopsParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&COPS_REGEX, lines)?;
+        // A bare `+COPS: <mode>` while no operator is selected
+        let (Some(format), Some(operator)) = (caps.get(2), caps.get(3)) else { return Ok(()) };
+        let operator = operator.as_str();
+        // Format 2: numeric MCC and MNC instead of a name
+        if format.as_str() == "2" {
+            info.network = Plmn::from_numeric(operator);
+        }
+        // `<AcT>` is left out by modems that support a single one
+        let tech = caps.get(4).map_or("", |tech| tech.as_str());
+        // `<AcT>` of 3GPP TS 27.007 clause 7.3
+        let mode = match tech {
+            "0" | "1" | "8" => Some("GSM"),
+            "2" => Some("UMTS"),
+            "3" => Some("EDGE"),
+            "4" => Some("HSDPA"),
+            "5" => Some("HSUPA"),
+            "6" => Some("HSPA"),
+            "7" | "9" => Some("LTE"),
+            "10" => Some("LTE (5GC)"),
+            "11" | "12" => Some("NR"),
+            "13" => Some("EN-DC"),
+            _ => None,
+        };
+        info.nr_mode = match tech {
+            "11" | "12" => NrMode::Sa,
+            "13" => NrMode::EnDc,
+            _ => NrMode::None,
+        };
+        info.operator = Some(info.network.as_ref()
+            .and_then(|network| network.operator)
+            .unwrap_or(operator)
+            .to_string());
+        info.mode = mode.map(str::to_string);
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::ModemInfo;
+
+pub static CSQ_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CSQ: (\d+),(\d+)"#).unwrap());
+
+/// Signal quality, `<rssi>,<ber>`
+pub struct CsqParser;
+
+impl ResponseParser for CsqParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&CSQ_REGEX, lines)?;
+        info.csq = caps[1].parse().ok();
+        // 99: not known or not detectable
+        info.csq_perc = info.csq.filter(|csq| (0..=31).contains(csq)).map(|csq| csq * 100 / 31);
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::supported_modems::{GTACT_RATS, LTE_BAND_OFFSET, NR_BAND_OFFSET};
+use crate::modem_tools::types::{BandModes, ModemInfo};
+
+// <rat>,<preferred rat>,<preferred rat 2>[,<band>...]
+pub static GTACT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+GTACT: (?P<rat>\d+),(?P<pref1>\d*),(?P<pref2>\d*)(?P<bands>(?:,\d+)*)"#).unwrap());
+
+/// Enabled access technologies and bands of the Fibocom FM350-GL
+pub struct GtactParser;
+
+impl ResponseParser for GtactParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&GTACT_REGEX, lines)?;
+
+        let rat = caps["rat"].parse::<u8>().unwrap_or(0);
+        if let Some(&((umts, lte, nr), _)) = GTACT_RATS.iter().find(|&&(_, value)| value == rat) {
+            info.config_band_modes = [(umts, BandModes::Umts), (lte, BandModes::Lte), (nr, BandModes::Nr)]
+                .into_iter()
+                .filter_map(|(enabled, mode)| enabled.then_some(mode))
+                .collect();
+        }
+
+        for band in caps["bands"].split(',').filter_map(|band| band.parse::<usize>().ok()) {
+            match band {
+                0 => {}
+                band if band > NR_BAND_OFFSET => info.enabled_nr_bands.push(band - NR_BAND_OFFSET),
+                band if band > LTE_BAND_OFFSET => info.enabled_lte_bands.push(band - LTE_BAND_OFFSET),
+                band => info.enabled_umts_bands.push(band),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::converters::{get_band_lte, parse_bandwidth};
+use crate::modem_tools::parsers::{MalformedLine, ResponseParser};
+use crate::modem_tools::supported_modems::{LTE_BAND_OFFSET, NR_BAND_OFFSET};
+use crate::modem_tools::types::ModemInfo;
+
+// PCC:<band>,<pci>,<arfcn>,<dl bandwidth>,<ul bandwidth>
+pub static GTCAINFO_PCC_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"PCC:\s*(?P<band>\d+),(?P<pci>[0-9A-Fa-f]+),(?P<arfcn>\d+),(?P<bw>\d+)"#).unwrap());
+// SCC<n>:<state>,<ul configured>,<band>,<pci>,<arfcn>,<dl bandwidth>,<ul bandwidth>
+pub static GTCAINFO_SCC_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"SCC\d+:\s*(?P<state>\d+),(?P<ul>\d+),(?P<band>\d+),(?P<pci>[0-9A-Fa-f]+),(?P<arfcn>\d+),(?P<bw>\d+)"#).unwrap());
+
+fn carrier_band(band: usize, arfcn: i32, bandwidth: &str) -> String {
+    if band > NR_BAND_OFFSET {
+        format!("n{}@{}MHz", band - NR_BAND_OFFSET, bandwidth)
+    } else if band > LTE_BAND_OFFSET {
+        format!("B{}@{}MHz", band - LTE_BAND_OFFSET, parse_bandwidth(bandwidth))
+    } else {
+        format!("{}@{}MHz", get_band_lte(arfcn), parse_bandwidth(bandwidth))
+    }
+}
+
+/// Carrier aggregation of the Fibocom FM350-GL, the primary cell and the secondary ones
+pub struct GtcainfoParser;
+
+impl ResponseParser for GtcainfoParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        // Without an LTE connection there is no primary cell to list
+        let Some(pcc) = lines.iter().find_map(|line| GTCAINFO_PCC_REGEX.captures(line)) else { return Ok(()) };
+
+        let mut band_info = carrier_band(
+            pcc["band"].parse().unwrap_or(0),
+            pcc["arfcn"].parse().unwrap_or(0),
+            &pcc["bw"],
+        );
+        // A deconfigured SCell (state 0) is not aggregated
+        for scc in lines.iter().filter_map(|line| GTCAINFO_SCC_REGEX.captures(line)).filter(|scc| &scc["state"] != "0") {
+            band_info.push(' ');
+            band_info.push_str(&carrier_band(
+                scc["band"].parse().unwrap_or(0),
+                scc["arfcn"].parse().unwrap_or(0),
+                &scc["bw"],
+            ));
+        }
+        info.band = Some(band_info);
+        Ok(())
+    }
+}
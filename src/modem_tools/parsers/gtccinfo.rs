@@ -0,0 +1,113 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::converters::{bandwidth_mhz, convert_rsrp_to_rssi, get_band_lte, get_band_nr, get_band_umts, parse_bandwidth, split_eci};
+use crate::modem_tools::parsers::{MalformedLine, ResponseParser};
+use crate::modem_tools::plmn::Plmn;
+use crate::modem_tools::supported_modems::NR_BAND_OFFSET;
+use crate::modem_tools::types::{CarrierInfo, ModemInfo, NrCarrierInfo, NrMode};
+
+const RAT_UMTS: &str = "2";
+const RAT_LTE: &str = "4";
+const RAT_NR: &str = "9";
+
+// <is service cell>,<rat>,<mcc>,<mnc>,<tac>,<cell id>,<arfcn>,<pci>,<band>,<bandwidth>,<sinr>,<rxlev>,<rsrp>,<rsrq>
+pub static GTCCINFO_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?m)^(?P<service>[12]),(?P<rat>\d+),(?P<mcc>\d*),(?P<mnc>\d*),(?P<tac>[0-9A-Fa-f]*),(?P<ci>[0-9A-Fa-f]*),(?P<arfcn>\d+),(?P<pci>[0-9A-Fa-f]+),(?P<band>\d*),(?P<bw>\d*),(?P<sinr>\d*),(?P<rxlev>\d*),(?P<rsrp>\d*),(?P<rsrq>\d*)"#).unwrap());
+
+/// Measurement in the 3GPP reporting range, 255 or empty when not available
+fn measurement(value: &str) -> Option<i32> {
+    value.parse::<i32>().ok().filter(|&value| value != 255)
+}
+
+/// Serving and neighbour cells of the Fibocom FM350-GL, LTE and NR
+pub struct GtccinfoParser;
+
+impl ResponseParser for GtccinfoParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let mut lte_serving = false;
+        let mut nr_serving = false;
+
+        // Lines without a cell, "LTE service cell:" and the like, name the list that follows
+        for caps in lines.iter().filter_map(|line| GTCCINFO_REGEX.captures(line)) {
+            let serving = &caps["service"] == "1";
+            let arfcn = caps["arfcn"].parse::<i32>().unwrap_or(0);
+            let pci = i32::from_str_radix(&caps["pci"], 16).unwrap_or(0);
+            if serving && info.network.is_none() {
+                info.network = Plmn::new(&caps["mcc"], &caps["mnc"]);
+            }
+
+            match &caps["rat"] {
+                // Only the channel is read, the measurement columns differ from LTE
+                RAT_UMTS if serving && !lte_serving && !nr_serving => {
+                    info.dluarfnc = Some(arfcn);
+                    info.band = Some(get_band_umts(arfcn));
+                }
+                RAT_LTE => {
+                    // 36.133: RSRP_00 < -140 dBm, RSRQ_00 < -19.5 dB, RS-SINR_00 < -23 dB, 1 or 0.5 dB steps
+                    let ci = i32::from_str_radix(&caps["ci"], 16).unwrap_or(0);
+                    let cell = split_eci(ci);
+                    let carrier = CarrierInfo {
+                        ci,
+                        enb_id: cell.map(|(enb_id, _)| enb_id),
+                        local_cell_id: cell.map(|(_, cell_id)| cell_id),
+                        pci,
+                        earfcn: arfcn,
+                        rsrp: measurement(&caps["rsrp"]).map_or(0, |rsrp| rsrp - 141),
+                        rsrq: measurement(&caps["rsrq"]).map_or(0, |rsrq| rsrq / 2 - 20),
+                        sinr: measurement(&caps["sinr"]).map_or(0, |sinr| (sinr - 47) / 2),
+                        bandwidth: bandwidth_mhz(&caps["bw"]),
+                        // +GTCCINFO has no timing advance
+                        timing_advance: None,
+                        distance: None,
+                    };
+                    if serving && !lte_serving {
+                        lte_serving = true;
+                        info.rsrp = measurement(&caps["rsrp"]).map(|rsrp| rsrp - 141);
+                        info.rsrq = measurement(&caps["rsrq"]).map(|rsrq| rsrq / 2 - 20);
+                        info.sinr = measurement(&caps["sinr"]).map(|sinr| (sinr - 47) / 2);
+                        info.dluarfnc = Some(arfcn);
+                        let bandwidth = caps["bw"].parse::<i32>().ok();
+                        info.rssi = info.rsrp.zip(bandwidth).and_then(|(rsrp, bandwidth)| convert_rsrp_to_rssi(rsrp, bandwidth));
+                        info.band = Some(format!("{}@{}MHz", get_band_lte(arfcn), parse_bandwidth(&caps["bw"])));
+                        info.carriers.insert(0, carrier);
+                    } else {
+                        info.carriers.push(carrier);
+                    }
+                }
+                RAT_NR => {
+                    let band = caps["band"].parse::<usize>().ok()
+                        .filter(|&band| band > NR_BAND_OFFSET)
+                        .map(|band| band - NR_BAND_OFFSET)
+                        .or_else(|| get_band_nr(arfcn));
+                    // 38.133: SS-RSRP_0 < -156 dBm, SS-RSRQ_0 < -43 dB, SS-SINR_0 < -23 dB
+                    let carrier = NrCarrierInfo {
+                        ci: i64::from_str_radix(&caps["ci"], 16).unwrap_or(0),
+                        gnb_id: None,
+                        local_cell_id: None,
+                        pci,
+                        nr_arfcn: arfcn,
+                        band,
+                        bandwidth: caps["bw"].parse().unwrap_or(0),
+                        ss_rsrp: measurement(&caps["rsrp"]).map_or(0, |rsrp| rsrp - 157),
+                        ss_rsrq: measurement(&caps["rsrq"]).map_or(0, |rsrq| (rsrq - 87) / 2),
+                        ss_sinr: measurement(&caps["sinr"]).map_or(0, |sinr| (sinr - 47) / 2),
+                    };
+                    if serving && !nr_serving {
+                        nr_serving = true;
+                        info.nr_carriers.insert(0, carrier);
+                    } else {
+                        info.nr_carriers.push(carrier);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        match (lte_serving, nr_serving) {
+            (true, true) => info.nr_mode = NrMode::EnDc,
+            (false, true) => info.nr_mode = NrMode::Sa,
+            // Without an NR serving cell keep what +COPS reported
+            _ => {}
+        }
+        Ok(())
+    }
+}
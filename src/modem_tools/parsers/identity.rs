@@ -0,0 +1,124 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::plmn::Plmn;
+use crate::modem_tools::types::ModemInfo;
+
+pub static CGMI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGMI: "?([^"\r\n]+)"?"#).unwrap());
+pub static CGMM_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGMM: "?([^"\r\n]+)"?"#).unwrap());
+pub static CGMR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGMR: "?([^"\r\n]+)"?"#).unwrap());
+pub static FMM_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+FMM: "([^"]+)"(?:,"([^"]+)")?"#).unwrap());
+pub static GTPKGVER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+GTPKGVER: "?([^"\r\n]+)"?"#).unwrap());
+pub static CFSN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CFSN: "?([^"\r\n]+)"?"#).unwrap());
+pub static CGSN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CGSN: "?([^"\r\n]+)"?"#).unwrap());
+pub static CIMI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CIMI: (\d+)"#).unwrap());
+pub static CCID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+CCID: (\d+)"#).unwrap());
+
+/// Most modems leave the `+CMD:` prefix out of identity answers: `Quectel`, `"Quectel"`
+/// or `Revision: EC25EFAR06A06M4G` give the value as well, and the prefixed form is not
+/// always quoted either
+fn value(regex: &Regex, lines: &[&str]) -> Result<Option<String>, MalformedLine> {
+    let line = lines.first().copied().unwrap_or_default();
+    if line.starts_with('+') {
+        return Ok(Some(captures(regex, lines)?[1].to_string()));
+    }
+    let value = line.split_once(": ").map_or(line, |(_, value)| value).trim().trim_matches('"');
+    match value.is_empty() {
+        true => Err(MalformedLine(line.to_string())),
+        false => Ok(Some(value.to_string())),
+    }
+}
+
+/// Manufacturer
+pub struct CgmiParser;
+
+impl ResponseParser for CgmiParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.manufacturer = value(&CGMI_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// Model, standard form
+pub struct CgmmParser;
+
+impl ResponseParser for CgmmParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.model = value(&CGMM_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// Firmware version, standard form
+pub struct CgmrParser;
+
+impl ResponseParser for CgmrParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.fw_version = value(&CGMR_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// Model, `+FMM: "L850 LTE Module","L850"`
+pub struct FmmParser;
+
+impl ResponseParser for FmmParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&FMM_REGEX, lines)?;
+        // The short name is the second string when there is one
+        info.model = caps.get(2).or(caps.get(1)).map(|model| model.as_str().to_string());
+        Ok(())
+    }
+}
+
+/// Firmware version
+pub struct GtpkgverParser;
+
+impl ResponseParser for GtpkgverParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.fw_version = value(&GTPKGVER_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// Serial number
+pub struct CfsnParser;
+
+impl ResponseParser for CfsnParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.serial_number = value(&CFSN_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// IMEI
+pub struct CgsnParser;
+
+impl ResponseParser for CgsnParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.imei = value(&CGSN_REGEX, lines)?;
+        Ok(())
+    }
+}
+
+/// IMSI, which also gives the home network
+pub struct CimiParser;
+
+impl ResponseParser for CimiParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let imsi = value(&CIMI_REGEX, lines)?;
+        info.home_network = imsi.as_deref().and_then(Plmn::from_imsi);
+        info.imsi = imsi;
+        Ok(())
+    }
+}
+
+/// SIM ICCID
+pub struct CcidParser;
+
+impl ResponseParser for CcidParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        info.iccid = value(&CCID_REGEX, lines)?;
+        Ok(())
+    }
+}
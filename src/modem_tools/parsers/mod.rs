@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use regex::{Captures, Regex};
+use crate::modem_tools::at_response::FinalResult;
+use crate::modem_tools::registration::RegistrationDomain;
+use crate::modem_tools::types::ModemInfo;
+
+pub mod cesq;
+pub mod cgdcont;
+pub mod cgcontrdp;
+pub mod cops;
+pub mod csq;
+pub mod gtact;
+pub mod gtcainfo;
+pub mod gtccinfo;
+pub mod identity;
+pub mod registration;
+pub mod xact;
+pub mod xlec;
+pub mod xmci;
+
+use cesq::CesqParser;
+use cgdcont::{CgdcontParser, CgpaddrParser};
+use cgcontrdp::CgcontrdpParser;
+use cops::CopsParser;
+use csq::CsqParser;
+use gtact::GtactParser;
+use gtcainfo::GtcainfoParser;
+use gtccinfo::GtccinfoParser;
+use identity::{CcidParser, CfsnParser, CgmiParser, CgmmParser, CgmrParser, CgsnParser, CimiParser, FmmParser, GtpkgverParser};
+use registration::RegistrationParser;
+use xact::XactParser;
+use xlec::XlecParser;
+use xmci::XmciParser;
+
+/// Response line a parser could not read
+#[derive(Debug, PartialEq)]
+pub struct MalformedLine(pub String);
+
+/// Reads the response of one AT command into `ModemInfo`
+pub trait ResponseParser: Sync {
+    /// `lines` are the lines of this response only: the ones starting with its prefix
+    /// and the lines without a prefix that follow them.
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine>;
+}
+
+/// Parsers keyed by response prefix. Every profile shares the table, a parser only runs
+/// when its response is in the poll output.
+///
+/// They run in this order and later ones build on what earlier ones read: `+XMCI` and
+/// `+GTCCINFO` only fill in the network when `+COPS` did not give it, `+XLEC` needs the
+/// `+XMCI` carriers, `+GTCAINFO` replaces the band `+GTCCINFO` read.
+pub static PARSERS: [(&str, &dyn ResponseParser); 25] = [
+    ("+CGMI", &CgmiParser),
+    ("+CGMM", &CgmmParser),
+    ("+CGMR", &CgmrParser),
+    ("+FMM", &FmmParser),
+    ("+GTPKGVER", &GtpkgverParser),
+    ("+CFSN", &CfsnParser),
+    ("+CGSN", &CgsnParser),
+    ("+CGCONTRDP", &CgcontrdpParser),
+    ("+CGDCONT", &CgdcontParser),
+    ("+CGPADDR", &CgpaddrParser),
+    ("+CIMI", &CimiParser),
+    ("+CCID", &CcidParser),
+    ("+COPS", &CopsParser),
+    ("+CSQ", &CsqParser),
+    ("+CESQ", &CesqParser),
+    ("+CREG", &RegistrationParser(RegistrationDomain::Cs)),
+    ("+CGREG", &RegistrationParser(RegistrationDomain::Ps)),
+    ("+CEREG", &RegistrationParser(RegistrationDomain::Eps)),
+    ("+C5GREG", &RegistrationParser(RegistrationDomain::Fivegs)),
+    ("+XMCI", &XmciParser),
+    ("+XLEC", &XlecParser),
+    ("+XACT", &XactParser),
+    ("+GTCCINFO", &GtccinfoParser),
+    ("+GTCAINFO", &GtcainfoParser),
+    ("+GTACT", &GtactParser),
+];
+
+/// Lines of the concatenated responses grouped by response prefix
+pub struct Responses<'a> {
+    lines: HashMap<&'a str, Vec<&'a str>>,
+}
+
+/// `AT+CGMI`, `AT+CSQ?` and `AT+CGCONTRDP=1` answer with `+CGMI`, `+CSQ` and `+CGCONTRDP`;
+/// for a compound command line the first command counts
+fn echoed_prefix(echo: &str) -> Option<&str> {
+    let command = echo.get(2..)?;
+    let end = command.find(['?', '=', ';']).unwrap_or(command.len());
+    Some(command[..end].trim()).filter(|prefix| prefix.starts_with('+'))
+}
+
+impl<'a> Responses<'a> {
+    pub fn new(info_string: &'a str) -> Responses<'a> {
+        let mut lines: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut current: Option<&str> = None;
+        for line in info_string.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if FinalResult::parse(line).is_some() {
+                current = None;
+                continue;
+            }
+            // Identity commands of some modems answer without a prefix, such lines
+            // belong to the command echoed before them
+            if line.to_ascii_uppercase().starts_with("AT") && !line.contains(':') {
+                current = echoed_prefix(line);
+                continue;
+            }
+            if let Some((prefix, _)) = line.split_once(':').filter(|_| line.starts_with('+')) {
+                current = Some(prefix);
+            }
+            if let Some(prefix) = current {
+                lines.entry(prefix).or_default().push(line);
+            }
+        }
+        Responses { lines }
+    }
+
+    pub fn get(&self, prefix: &str) -> Option<&[&'a str]> {
+        self.lines.get(prefix).map(Vec::as_slice)
+    }
+}
+
+/// `regex` applied to the first line it matches, single line responses
+fn captures<'l>(regex: &Regex, lines: &[&'l str]) -> Result<Captures<'l>, MalformedLine> {
+    lines.iter()
+        .find_map(|line| regex.captures(line))
+        .ok_or_else(|| MalformedLine(lines.first().unwrap_or(&"").to_string()))
+}
@@ -0,0 +1,15 @@
+use crate::modem_tools::parsers::{MalformedLine, ResponseParser};
+use crate::modem_tools::registration::{Registration, RegistrationDomain};
+use crate::modem_tools::types::ModemInfo;
+
+/// Network registration of one domain, `+CREG?`, `+CGREG?`, `+CEREG?` or `+C5GREG?`
+pub struct RegistrationParser(pub RegistrationDomain);
+
+impl ResponseParser for RegistrationParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let line = lines.first().copied().unwrap_or_default();
+        let registration = Registration::from_response(self.0, line).ok_or_else(|| MalformedLine(line.to_string()))?;
+        info.registration.set(self.0, registration);
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::{BandModes, ModemInfo};
+
+pub static XACT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+XACT: (?P<umts_flag>4?),?(?P<lte_flag>2?),?\d+,(?P<umts_bands>(?:\d{1,2},)*\d{1,2})?,,?(?P<lte_bands>(?:1\d{2},)*1\d{2})?\r?"#).unwrap());
+
+/// Enabled access technologies and bands of the Intel modules, LTE bands are 100 + band
+pub struct XactParser;
+
+impl ResponseParser for XactParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&XACT_REGEX, lines)?;
+        // Either list is left out when that access technology is disabled
+        info.enabled_umts_bands = caps.name("umts_bands").map_or(Vec::new(), |bands| {
+            bands.as_str().split(',').filter_map(|band| band.trim().parse::<usize>().ok()).collect()
+        });
+        info.enabled_lte_bands = caps.name("lte_bands").map_or(Vec::new(), |bands| {
+            bands.as_str().split(',').filter_map(|band| band.parse::<usize>().ok()?.checked_sub(100)).collect()
+        });
+        info.config_band_modes = ["umts_flag", "lte_flag"]
+            .iter()
+            .filter_map(|&flag| match (flag, caps.name(flag)?.as_str()) {
+                ("umts_flag", "4") => Some(BandModes::Umts),
+                ("lte_flag", "2") => Some(BandModes::Lte),
+                _ => None,
+            })
+            .collect();
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::converters::{bandwidth_mhz, convert_rsrp_to_rssi, get_band_lte, lte_channel, parse_bandwidth};
+use crate::modem_tools::parsers::{captures, MalformedLine, ResponseParser};
+use crate::modem_tools::types::{CarrierInfo, ModemInfo};
+
+pub static XLEC_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+XLEC: (?:\d+),(?P<no_of_cells>\d+),(?P<bw>(?:\d+,?)+),BAND_LTE_(?P<band>(?:\d+,?)+)"#).unwrap());
+
+/// `+XLEC` gives the bandwidth per component carrier, primary first, with its band but
+/// without the EARFCN. Each one is matched to the first carrier of that band, or by
+/// position when the bands are missing, and the bandwidth is set on every cell of
+/// that frequency: intra-frequency neighbours share it.
+fn assign_bandwidths(carriers: &mut [CarrierInfo], component_carriers: &[(Option<usize>, Option<f64>)]) {
+    let mut frequencies: Vec<(i32, f64)> = Vec::new();
+    for (i, &(band, bandwidth)) in component_carriers.iter().enumerate() {
+        let Some(bandwidth) = bandwidth else { continue };
+        let unassigned = |carrier: &&CarrierInfo| !frequencies.iter().any(|&(earfcn, _)| earfcn == carrier.earfcn);
+        let carrier = match band {
+            _ if i == 0 => carriers.first(),
+            Some(band) => carriers.iter().filter(unassigned).find(|carrier| lte_channel(carrier.earfcn).is_some_and(|channel| channel.band == band)),
+            None => carriers.get(i).filter(unassigned),
+        };
+        if let Some(carrier) = carrier {
+            frequencies.push((carrier.earfcn, bandwidth));
+        }
+    }
+    for carrier in carriers.iter_mut() {
+        carrier.bandwidth = frequencies.iter().find(|&&(earfcn, _)| earfcn == carrier.earfcn).map(|&(_, bandwidth)| bandwidth);
+    }
+}
+
+/// Carrier aggregation: number of component carriers, their bandwidths and bands.
+/// Runs after `XmciParser`, the bandwidths are assigned to its carriers.
+pub struct XlecParser;
+
+impl ResponseParser for XlecParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        let caps = captures(&XLEC_REGEX, lines)?;
+        let ca_number = caps["no_of_cells"].parse::<usize>().unwrap_or(1);
+
+        let ca_bw_x: Vec<_> = caps["bw"].split(',').collect::<Vec<_>>();
+        let ca_band_x: Vec<_> = caps["band"].split(',').map(|s| s.to_string()).collect();
+
+        let mut band_info = String::new();
+
+        // More or fewer bands than cells: the bands are taken from the carriers by position
+        // and the line is still reported as malformed
+        let mismatch = ca_band_x.len() != ca_number;
+        if mismatch {
+            for (i, bw_str) in ca_bw_x.iter().enumerate().take(ca_number) {
+                let bw = parse_bandwidth(bw_str);
+                let band = info.carriers.get(i).map_or("--".to_string(), |carrier| get_band_lte(carrier.earfcn));
+                band_info.push_str(&format!("{}@{}MHz ", band, bw));
+            }
+        } else {
+            for (i, bw_str) in ca_bw_x.iter().enumerate().take(ca_number) {
+                let bw = parse_bandwidth(bw_str);
+                let band = ca_band_x.get(i).map_or("--".to_string(), |b| format!("B{}", b));
+                band_info.push_str(&format!("{}@{}MHz ", band, bw));
+            }
+        }
+
+        let component_carriers: Vec<(Option<usize>, Option<f64>)> = ca_bw_x.iter().enumerate().take(ca_number)
+            .map(|(i, bw_str)| {
+                let band = if ca_band_x.len() == ca_number { ca_band_x.get(i).and_then(|b| b.parse().ok()) } else { None };
+                (band, bandwidth_mhz(bw_str))
+            })
+            .collect();
+        assign_bandwidths(&mut info.carriers, &component_carriers);
+
+        // RSSI over the bandwidth of the primary carrier
+        let primary_bandwidth = ca_bw_x.first().and_then(|bw| bw.parse::<i32>().ok());
+        info.rssi = info.rsrp.zip(primary_bandwidth).and_then(|(rsrp, bw)| convert_rsrp_to_rssi(rsrp, bw));
+
+        info.band = Some(band_info);
+        match mismatch {
+            true => Err(MalformedLine(caps[0].to_string())),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::converters::{hex_to_decimal, parse_timing_advance, split_eci};
+use crate::modem_tools::parsers::{MalformedLine, ResponseParser};
+use crate::modem_tools::plmn::Plmn;
+use crate::modem_tools::types::{CarrierInfo, ModemInfo};
+
+pub static XMCI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+XMCI: (?P<type>[45]),(?P<mcc>\d+),(?P<mnc>\d+),"(?P<tac>[^"]*)","(?P<ci_x>[^"]*)","(?P<pci_x>[^"]*)","(?P<dluarfnc_x>[^"]*)","(?P<earfcn_ul>[^"]*)","(?P<pathloss_lte>[^"]*)",(?P<rsrp>\d+),(?P<rsrq>\d+),(?P<sinr>-?\d+),"(?P<timing_advance>[^"]*)","(?P<cqi>[^"]*)""#).unwrap());
+
+/// GSM serving cell: `<LAC>`, `<CI>`, `<BSIC>`, ARFCNs and path loss, `<RXLEV>`, `<BER>` and `<TA>`
+pub static XMCI_GSM_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+XMCI: 0,(?P<mcc>\d+),(?P<mnc>\d+),(?:"[^"]*",){6}(?P<rxlev>\d+),(?P<ber>\d+),"(?P<timing_advance>[^"]*)""#).unwrap());
+
+/// LTE cells, type 4 the serving ones and type 5 the neighbours, and the timing advance
+/// of the GSM serving cell (type 0). GSM neighbours and UMTS cells (types 1 to 3) are skipped.
+pub struct XmciParser;
+
+impl ResponseParser for XmciParser {
+    fn parse(&self, lines: &[&str], info: &mut ModemInfo) -> Result<(), MalformedLine> {
+        if let Some(line) = lines.iter().find(|line| line.starts_with("+XMCI: 0,")) {
+            let caps = XMCI_GSM_REGEX.captures(line).ok_or_else(|| MalformedLine(line.to_string()))?;
+            info.gsm_timing_advance = parse_timing_advance(&caps["timing_advance"]);
+        }
+        let mut primary = true;
+        for line in lines.iter().filter(|line| line.starts_with("+XMCI: 4,") || line.starts_with("+XMCI: 5,")) {
+            let caps = XMCI_REGEX.captures(line).ok_or_else(|| MalformedLine(line.to_string()))?;
+            let dluarfnc_x: i32 = hex_to_decimal(&caps["dluarfnc_x"]).unwrap_or(0);
+            let ci = hex_to_decimal(&caps["ci_x"]).unwrap_or(0);
+            let cell = split_eci(ci);
+
+            // The first serving cell is the primary one
+            if &caps["type"] == "4" && primary {
+                primary = false;
+                info.rsrp = caps["rsrp"].parse::<i32>().ok().map(|rsrp| rsrp - 141);
+                info.rsrq = caps["rsrq"].parse::<i32>().ok().map(|rsrq| rsrq / 2 - 20);
+                info.sinr = caps["sinr"].parse::<i32>().ok().map(|sinr| sinr / 2);
+                info.dluarfnc = Some(dluarfnc_x);
+                if info.network.is_none() {
+                    info.network = Plmn::new(&caps["mcc"], &caps["mnc"]);
+                }
+            }
+
+            info.carriers.push(CarrierInfo {
+                ci,
+                enb_id: cell.map(|(enb_id, _)| enb_id),
+                local_cell_id: cell.map(|(_, cell_id)| cell_id),
+                pci: hex_to_decimal(&caps["pci_x"]).unwrap_or(0),
+                earfcn: dluarfnc_x,
+                rsrp: caps["rsrp"].parse::<i32>().unwrap_or(0) - 141,
+                rsrq: caps["rsrq"].parse::<i32>().unwrap_or(0) / 2 - 20,
+                sinr: caps["sinr"].parse::<i32>().unwrap_or(0) / 2,
+                bandwidth: None,
+                timing_advance: parse_timing_advance(&caps["timing_advance"]),
+                distance: None,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+use serde::Serialize;
+
+/// Mobile country codes, ITU-T E.212. Some countries have several.
+static COUNTRIES: &[(u16, &str)] = &[
+    (202, "Greece"), (204, "Netherlands"), (206, "Belgium"), (208, "France"), (212, "Monaco"),
+    (213, "Andorra"), (214, "Spain"), (216, "Hungary"), (218, "Bosnia and Herzegovina"), (219, "Croatia"),
+    (220, "Serbia"), (221, "Kosovo"), (222, "Italy"), (225, "Vatican"), (226, "Romania"),
+    (228, "Switzerland"), (230, "Czech Republic"), (231, "Slovakia"), (232, "Austria"), (234, "United Kingdom"),
+    (235, "United Kingdom"), (238, "Denmark"), (240, "Sweden"), (242, "Norway"), (244, "Finland"),
+    (246, "Lithuania"), (247, "Latvia"), (248, "Estonia"), (250, "Russia"), (255, "Ukraine"),
+    (257, "Belarus"), (259, "Moldova"), (260, "Poland"), (262, "Germany"), (266, "Gibraltar"),
+    (268, "Portugal"), (270, "Luxembourg"), (272, "Ireland"), (274, "Iceland"), (276, "Albania"),
+    (278, "Malta"), (280, "Cyprus"), (282, "Georgia"), (283, "Armenia"), (284, "Bulgaria"),
+    (286, "Turkey"), (288, "Faroe Islands"), (290, "Greenland"), (292, "San Marino"), (293, "Slovenia"),
+    (294, "North Macedonia"), (295, "Liechtenstein"), (297, "Montenegro"),
+    (302, "Canada"), (308, "Saint Pierre and Miquelon"), (310, "United States"), (311, "United States"),
+    (312, "United States"), (313, "United States"), (314, "United States"), (315, "United States"),
+    (316, "United States"), (330, "Puerto Rico"), (334, "Mexico"), (338, "Jamaica"), (340, "French Antilles"),
+    (342, "Barbados"), (344, "Antigua and Barbuda"), (346, "Cayman Islands"), (348, "British Virgin Islands"),
+    (350, "Bermuda"), (352, "Grenada"), (354, "Montserrat"), (356, "Saint Kitts and Nevis"), (358, "Saint Lucia"),
+    (360, "Saint Vincent and the Grenadines"), (362, "Curaçao"), (363, "Aruba"), (364, "Bahamas"),
+    (365, "Anguilla"), (366, "Dominica"), (368, "Cuba"), (370, "Dominican Republic"), (372, "Haiti"),
+    (374, "Trinidad and Tobago"), (376, "Turks and Caicos Islands"),
+    (400, "Azerbaijan"), (401, "Kazakhstan"), (402, "Bhutan"), (404, "India"), (405, "India"),
+    (406, "India"), (410, "Pakistan"), (412, "Afghanistan"), (413, "Sri Lanka"), (414, "Myanmar"),
+    (415, "Lebanon"), (416, "Jordan"), (417, "Syria"), (418, "Iraq"), (419, "Kuwait"),
+    (420, "Saudi Arabia"), (421, "Yemen"), (422, "Oman"), (424, "United Arab Emirates"), (425, "Israel"),
+    (426, "Bahrain"), (427, "Qatar"), (428, "Mongolia"), (429, "Nepal"), (430, "United Arab Emirates"),
+    (431, "United Arab Emirates"), (432, "Iran"), (434, "Uzbekistan"), (436, "Tajikistan"), (437, "Kyrgyzstan"),
+    (438, "Turkmenistan"), (440, "Japan"), (441, "Japan"), (450, "South Korea"), (452, "Vietnam"),
+    (454, "Hong Kong"), (455, "Macau"), (456, "Cambodia"), (457, "Laos"), (460, "China"),
+    (466, "Taiwan"), (467, "North Korea"), (470, "Bangladesh"), (472, "Maldives"),
+    (502, "Malaysia"), (505, "Australia"), (510, "Indonesia"), (514, "Timor-Leste"), (515, "Philippines"),
+    (520, "Thailand"), (525, "Singapore"), (528, "Brunei"), (530, "New Zealand"), (536, "Nauru"),
+    (537, "Papua New Guinea"), (539, "Tonga"), (540, "Solomon Islands"), (541, "Vanuatu"), (542, "Fiji"),
+    (544, "American Samoa"), (545, "Kiribati"), (546, "New Caledonia"), (547, "French Polynesia"),
+    (548, "Cook Islands"), (549, "Samoa"), (550, "Micronesia"), (551, "Marshall Islands"), (552, "Palau"),
+    (602, "Egypt"), (603, "Algeria"), (604, "Morocco"), (605, "Tunisia"), (606, "Libya"),
+    (607, "Gambia"), (608, "Senegal"), (609, "Mauritania"), (610, "Mali"), (611, "Guinea"),
+    (612, "Côte d'Ivoire"), (613, "Burkina Faso"), (614, "Niger"), (615, "Togo"), (616, "Benin"),
+    (617, "Mauritius"), (618, "Liberia"), (619, "Sierra Leone"), (620, "Ghana"), (621, "Nigeria"),
+    (622, "Chad"), (623, "Central African Republic"), (624, "Cameroon"), (625, "Cape Verde"),
+    (626, "São Tomé and Príncipe"), (627, "Equatorial Guinea"), (628, "Gabon"), (629, "Congo"),
+    (630, "DR Congo"), (631, "Angola"), (632, "Guinea-Bissau"), (633, "Seychelles"), (634, "Sudan"),
+    (635, "Rwanda"), (636, "Ethiopia"), (637, "Somalia"), (638, "Djibouti"), (639, "Kenya"),
+    (640, "Tanzania"), (641, "Uganda"), (642, "Burundi"), (643, "Mozambique"), (645, "Zambia"),
+    (646, "Madagascar"), (647, "Réunion"), (648, "Zimbabwe"), (649, "Namibia"), (650, "Malawi"),
+    (651, "Lesotho"), (652, "Botswana"), (653, "Eswatini"), (654, "Comoros"), (655, "South Africa"),
+    (657, "Eritrea"), (659, "South Sudan"),
+    (702, "Belize"), (704, "Guatemala"), (706, "El Salvador"), (708, "Honduras"), (710, "Nicaragua"),
+    (712, "Costa Rica"), (714, "Panama"), (716, "Peru"), (722, "Argentina"), (724, "Brazil"),
+    (730, "Chile"), (732, "Colombia"), (734, "Venezuela"), (736, "Bolivia"), (738, "Guyana"),
+    (740, "Ecuador"), (744, "Paraguay"), (746, "Suriname"), (748, "Uruguay"), (750, "Falkland Islands"),
+    (901, "International"),
+];
+
+/// Brand names of the larger networks. The MNC is kept as written in the numbering
+/// plan, its length is what tells a 3 digit MNC from a 2 digit one in the IMSI.
+static OPERATORS: &[(u16, &str, &str)] = &[
+    // Europe
+    (202, "01", "Cosmote"), (202, "05", "Vodafone"), (202, "10", "Nova"),
+    (204, "04", "Vodafone"), (204, "08", "KPN"), (204, "16", "Odido"), (204, "20", "Odido"),
+    (206, "01", "Proximus"), (206, "10", "Orange"), (206, "20", "Base"),
+    (208, "01", "Orange"), (208, "10", "SFR"), (208, "15", "Free"), (208, "20", "Bouygues Telecom"),
+    (214, "01", "Vodafone"), (214, "03", "Orange"), (214, "04", "Yoigo"), (214, "07", "Movistar"),
+    (216, "01", "Yettel"), (216, "30", "Telekom"), (216, "70", "One"),
+    (218, "03", "HT Eronet"), (218, "05", "m:tel"), (218, "90", "BH Telecom"),
+    (219, "01", "Hrvatski Telekom"), (219, "02", "Telemach"), (219, "10", "A1"),
+    (220, "01", "Yettel"), (220, "03", "mt:s"), (220, "05", "A1"),
+    (222, "01", "TIM"), (222, "10", "Vodafone"), (222, "50", "Iliad"), (222, "88", "WindTre"),
+    (226, "01", "Vodafone"), (226, "03", "Telekom"), (226, "05", "Digi"), (226, "10", "Orange"),
+    (228, "01", "Swisscom"), (228, "02", "Sunrise"), (228, "03", "Salt"),
+    (230, "01", "T-Mobile"), (230, "02", "O2"), (230, "03", "Vodafone"),
+    (231, "01", "Orange"), (231, "02", "Telekom"), (231, "06", "O2"),
+    (232, "01", "A1"), (232, "03", "Magenta"), (232, "05", "Drei"), (232, "10", "Drei"),
+    (234, "10", "O2"), (234, "15", "Vodafone"), (234, "20", "Three"), (234, "30", "EE"), (234, "33", "EE"),
+    (238, "01", "TDC"), (238, "02", "Telenor"), (238, "06", "3"), (238, "20", "Telia"),
+    (240, "01", "Telia"), (240, "02", "Tre"), (240, "07", "Tele2"), (240, "08", "Telenor"),
+    (242, "01", "Telenor"), (242, "02", "Telia"),
+    (244, "05", "Elisa"), (244, "12", "DNA"), (244, "91", "Telia"),
+    (246, "01", "Telia"), (246, "02", "Bite"), (246, "03", "Tele2"),
+    (247, "01", "LMT"), (247, "02", "Tele2"), (247, "05", "Bite"),
+    (248, "01", "Telia"), (248, "02", "Elisa"), (248, "03", "Tele2"),
+    (250, "01", "MTS"), (250, "02", "MegaFon"), (250, "20", "T2"), (250, "99", "Beeline"),
+    (255, "01", "Vodafone"), (255, "03", "Kyivstar"), (255, "06", "lifecell"),
+    (257, "01", "A1"), (257, "02", "MTS"), (257, "04", "life:)"),
+    (259, "01", "Orange"), (259, "02", "Moldcell"),
+    (260, "01", "Plus"), (260, "02", "T-Mobile"), (260, "03", "Orange"), (260, "06", "Play"),
+    (262, "01", "Telekom"), (262, "02", "Vodafone"), (262, "03", "O2"), (262, "07", "O2"), (262, "08", "O2"),
+    (268, "01", "Vodafone"), (268, "03", "NOS"), (268, "06", "MEO"),
+    (270, "01", "POST"), (270, "77", "Tango"), (270, "99", "Orange"),
+    (272, "01", "Vodafone"), (272, "02", "Three"), (272, "03", "Eir"), (272, "05", "Three"),
+    (274, "01", "Síminn"), (274, "02", "Vodafone"), (274, "11", "Nova"),
+    (276, "01", "One"), (276, "02", "Vodafone"),
+    (278, "01", "Epic"), (278, "21", "GO"), (278, "77", "Melita"),
+    (280, "01", "Cytamobile-Vodafone"), (280, "10", "Epic"),
+    (282, "01", "Silknet"), (282, "02", "Magti"), (282, "04", "Cellfie"),
+    (283, "01", "Team"), (283, "05", "Viva"), (283, "10", "Ucom"),
+    (284, "01", "A1"), (284, "03", "Vivacom"), (284, "05", "Yettel"),
+    (286, "01", "Turkcell"), (286, "02", "Vodafone"), (286, "03", "Türk Telekom"),
+    (293, "40", "A1"), (293, "41", "Telekom Slovenije"), (293, "64", "T-2"), (293, "70", "Telemach"),
+    (294, "01", "Telekom"), (294, "03", "A1"),
+    (297, "01", "One"), (297, "02", "Crnogorski Telekom"), (297, "03", "m:tel"),
+    // North and South America
+    (302, "220", "Telus"), (302, "490", "Freedom"), (302, "500", "Vidéotron"), (302, "610", "Bell"),
+    (302, "720", "Rogers"), (302, "780", "SaskTel"),
+    (310, "004", "Verizon"), (310, "012", "Verizon"), (311, "480", "Verizon"),
+    (310, "150", "AT&T"), (310, "280", "AT&T"), (310, "410", "AT&T"), (313, "100", "FirstNet"),
+    (310, "120", "T-Mobile"), (310, "160", "T-Mobile"), (310, "260", "T-Mobile"), (311, "490", "T-Mobile"),
+    (311, "580", "UScellular"),
+    (334, "020", "Telcel"), (334, "030", "Movistar"), (334, "050", "AT&T"), (334, "090", "AT&T"),
+    (334, "140", "Altán Redes"),
+    (716, "06", "Movistar"), (716, "10", "Claro"), (716, "15", "Bitel"), (716, "17", "Entel"),
+    (722, "070", "Movistar"), (722, "310", "Claro"), (722, "320", "Claro"), (722, "330", "Claro"),
+    (722, "341", "Personal"),
+    (724, "02", "TIM"), (724, "03", "TIM"), (724, "04", "TIM"), (724, "05", "Claro"),
+    (724, "06", "Vivo"), (724, "10", "Vivo"), (724, "11", "Vivo"), (724, "31", "Oi"),
+    (730, "01", "Entel"), (730, "02", "Movistar"), (730, "03", "Claro"), (730, "09", "WOM"),
+    (732, "101", "Claro"), (732, "103", "Tigo"), (732, "111", "Tigo"), (732, "123", "Movistar"),
+    (732, "360", "WOM"),
+    // Asia and Oceania
+    (401, "01", "Beeline"), (401, "02", "Kcell"), (401, "77", "Tele2"),
+    (404, "10", "Airtel"), (404, "11", "Vi"), (404, "20", "Vi"), (404, "45", "Airtel"),
+    (405, "840", "Jio"), (405, "854", "Jio"), (405, "857", "Jio"), (405, "874", "Jio"),
+    (410, "01", "Jazz"), (410, "03", "Ufone"), (410, "04", "Zong"), (410, "06", "Telenor"),
+    (420, "01", "STC"), (420, "03", "Mobily"), (420, "04", "Zain"),
+    (424, "02", "e&"), (424, "03", "du"),
+    (425, "01", "Partner"), (425, "02", "Cellcom"), (425, "03", "Pelephone"),
+    (427, "01", "Ooredoo"), (427, "02", "Vodafone"),
+    (440, "10", "docomo"), (440, "11", "Rakuten Mobile"), (440, "20", "SoftBank"), (440, "50", "au"),
+    (440, "51", "au"),
+    (450, "05", "SK Telecom"), (450, "06", "LG U+"), (450, "08", "KT"),
+    (452, "01", "MobiFone"), (452, "02", "VinaPhone"), (452, "04", "Viettel"),
+    (454, "00", "CSL"), (454, "03", "3"), (454, "06", "SmarTone"), (454, "12", "CMHK"),
+    (460, "00", "China Mobile"), (460, "01", "China Unicom"), (460, "02", "China Mobile"),
+    (460, "03", "China Telecom"), (460, "07", "China Mobile"), (460, "11", "China Telecom"),
+    (460, "15", "China Broadnet"),
+    (466, "01", "FarEasTone"), (466, "92", "Chunghwa Telecom"), (466, "97", "Taiwan Mobile"),
+    (470, "01", "Grameenphone"), (470, "02", "Robi"), (470, "03", "Banglalink"),
+    (502, "12", "Maxis"), (502, "13", "CelcomDigi"), (502, "16", "CelcomDigi"), (502, "18", "U Mobile"),
+    (505, "01", "Telstra"), (505, "02", "Optus"), (505, "03", "Vodafone"),
+    (510, "01", "Indosat"), (510, "10", "Telkomsel"), (510, "11", "XL"), (510, "89", "3"),
+    (515, "02", "Globe"), (515, "03", "Smart"), (515, "66", "DITO"),
+    (520, "03", "AIS"), (520, "04", "TrueMove H"), (520, "05", "dtac"),
+    (525, "01", "Singtel"), (525, "03", "M1"), (525, "05", "StarHub"),
+    (530, "01", "One NZ"), (530, "05", "Spark"), (530, "24", "2degrees"),
+    // Africa
+    (602, "01", "Orange"), (602, "02", "Vodafone"), (602, "03", "Etisalat"), (602, "04", "WE"),
+    (604, "00", "Orange"), (604, "01", "Maroc Telecom"), (604, "02", "inwi"),
+    (621, "20", "Airtel"), (621, "30", "MTN"), (621, "50", "Glo"), (621, "60", "9mobile"),
+    (639, "02", "Safaricom"), (639, "03", "Airtel"), (639, "07", "Telkom"),
+    (655, "01", "Vodacom"), (655, "07", "Cell C"), (655, "10", "MTN"),
+];
+
+/// Countries that only allocate 3 digit MNCs
+const THREE_DIGIT_MNC_COUNTRIES: &[u16] = &[
+    302, 310, 311, 312, 313, 314, 315, 316, 334, 338, 342, 344, 346, 348, 352, 354, 356, 358, 360,
+    365, 366, 376, 708, 722, 732, 750,
+];
+
+/// Public land mobile network, with its country and brand when they are in the database.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Plmn {
+    pub mcc: String,
+    pub mnc: String,
+    pub country: Option<&'static str>,
+    pub operator: Option<&'static str>,
+}
+
+impl Plmn {
+    /// `mnc` may come without its leading zeros (`+XMCI` reports 220,3 as well as 220,03)
+    pub fn new(mcc: &str, mnc: &str) -> Option<Plmn> {
+        let mcc_code = mcc.parse::<u16>().ok().filter(|&mcc| (200..1000).contains(&mcc))?;
+        let mnc_code = mnc.parse::<u16>().ok().filter(|&mnc| mnc < 1000)?;
+        let operator = OPERATORS.iter()
+            .find(|&&(mcc, mnc, _)| mcc == mcc_code && mnc.parse() == Ok(mnc_code));
+        let digits = match operator {
+            Some((_, mnc, _)) => mnc.len(),
+            None if THREE_DIGIT_MNC_COUNTRIES.contains(&mcc_code) || mnc.len() == 3 => 3,
+            None => 2,
+        };
+        Some(Plmn {
+            mcc: format!("{:03}", mcc_code),
+            mnc: format!("{:0width$}", mnc_code, width = digits),
+            country: COUNTRIES.iter().find(|&&(mcc, _)| mcc == mcc_code).map(|&(_, country)| country),
+            operator: operator.map(|&(_, _, operator)| operator),
+        })
+    }
+
+    /// Numeric `+COPS` operator, MCC followed by a 2 or 3 digit MNC: `22003`
+    pub fn from_numeric(numeric: &str) -> Option<Plmn> {
+        if !(5..=6).contains(&numeric.len()) || !numeric.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Plmn::new(&numeric[..3], &numeric[3..])
+    }
+
+    /// Home network of the SIM. The IMSI does not say how long the MNC is, the
+    /// numbering plan of the country does.
+    pub fn from_imsi(imsi: &str) -> Option<Plmn> {
+        if imsi.len() < 6 || !imsi.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mcc = &imsi[..3];
+        let mcc_code = mcc.parse::<u16>().ok()?;
+        let three_digits = THREE_DIGIT_MNC_COUNTRIES.contains(&mcc_code)
+            || OPERATORS.iter().any(|&(mcc, mnc, _)| mcc == mcc_code && mnc == &imsi[3..6]);
+        Plmn::new(mcc, if three_digits { &imsi[3..6] } else { &imsi[3..5] })
+    }
+
+    fn same_network(&self, other: &Plmn) -> bool {
+        (self.mcc == other.mcc && self.mnc == other.mnc)
+            // One operator with several codes, like O2 Germany 262 03/07/08
+            || (self.mcc == other.mcc && self.operator.is_some() && self.operator == other.operator)
+    }
+}
+
+impl fmt::Display for Plmn {
+    /// `220 03 mt:s, Serbia`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.mcc, self.mnc)?;
+        match (self.operator, self.country) {
+            (Some(operator), Some(country)) => write!(f, " {}, {}", operator, country),
+            (None, Some(country)) => write!(f, " {}", country),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Serving network compared to the home network of the SIM
+#[derive(Default, Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Roaming {
+    /// Serving or home network not known yet
+    #[default]
+    Unknown,
+    Home,
+    /// Other network of the home country; also what a virtual operator on a host network looks like
+    National,
+    International,
+}
+
+impl Roaming {
+    pub fn between(home: Option<&Plmn>, serving: Option<&Plmn>) -> Roaming {
+        match (home, serving) {
+            (Some(home), Some(serving)) if home.same_network(serving) => Roaming::Home,
+            // India, the UK and the US have several MCCs
+            (Some(home), Some(serving)) if home.mcc == serving.mcc
+                || (home.country.is_some() && home.country == serving.country) => Roaming::National,
+            (Some(_), Some(_)) => Roaming::International,
+            _ => Roaming::Unknown,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Roaming::Unknown => "unknown",
+            Roaming::Home => "no",
+            Roaming::National => "national",
+            Roaming::International => "international",
+        }
+    }
+}
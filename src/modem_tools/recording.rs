@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::serial_tools::transport::Transport;
+
+// Session file format, one record per chunk of bytes as it went over the port:
+//   > <unix time ms> <bytes>        written to the modem
+//   < <unix time ms> <bytes>        read from the modem
+// The bytes are escaped as in Rust byte strings, `AT+CSQ?\r` or `\r\nOK\r\n`, so
+// echo, line endings and split reads are kept exactly.
+
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis())
+}
+
+/// Reverse of `<[u8]>::escape_ascii`
+fn unescape(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'x') => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                if let Some(byte) = std::str::from_utf8(&hex).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    bytes.push(byte);
+                }
+            }
+            Some(escaped) => bytes.push(escaped),
+            None => bytes.push(b'\\'),
+        }
+    }
+    bytes
+}
+
+/// Writes every chunk of bytes exchanged with the modem to a file.
+pub struct SessionRecorder {
+    file: BufWriter<File>,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &str) -> Result<SessionRecorder, io::Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "# modem-manager session recording")?;
+        Ok(SessionRecorder { file })
+    }
+
+    pub fn sent(&mut self, bytes: &[u8]) -> Result<(), io::Error> {
+        self.record('>', bytes)
+    }
+
+    pub fn received(&mut self, bytes: &[u8]) -> Result<(), io::Error> {
+        self.record('<', bytes)
+    }
+
+    fn record(&mut self, kind: char, bytes: &[u8]) -> Result<(), io::Error> {
+        writeln!(self.file, "{} {} {}", kind, timestamp(), bytes.escape_ascii())?;
+        // Keep the file usable if the app is killed mid-session
+        self.file.flush()
+    }
+}
+
+/// Transport that copies its traffic to the session recorder while one is set.
+pub struct RecordingTransport {
+    port: Box<dyn Transport>,
+    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+}
+
+impl RecordingTransport {
+    pub fn new(port: Box<dyn Transport>, recorder: Arc<Mutex<Option<SessionRecorder>>>) -> RecordingTransport {
+        RecordingTransport { port, recorder }
+    }
+}
+
+impl Read for RecordingTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.port.read(buf)?;
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut().filter(|_| count > 0) {
+            // Losing the recording must not break the session itself
+            let _ = recorder.received(&buf[..count]);
+        }
+        Ok(count)
+    }
+}
+
+impl Write for RecordingTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.port.write(buf)?;
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            let _ = recorder.sent(&buf[..count]);
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Transport for RecordingTransport {}
+
+#[derive(Debug)]
+enum Record {
+    Sent { at: u128, bytes: Vec<u8> },
+    Received { at: u128, bytes: Vec<u8> },
+}
+
+/// Plays a recorded session back as if it came from the modem.
+///
+/// The app has to write what was recorded as sent, in the same order; the bytes
+/// received after it are then read back in the same chunks and with the same
+/// delays. A different command fails the write, and reading past the end of the
+/// recording fails with `UnexpectedEof`, so a replay never makes answers up.
+pub struct ReplayTransport {
+    records: Vec<Record>,
+    position: usize,
+    input: Vec<u8>,
+    output: VecDeque<u8>,
+    /// When the last record was played, and when it had been recorded
+    played: (Instant, u128),
+    read_timeout: Duration,
+}
+
+impl ReplayTransport {
+    pub fn parse(recording: &str, read_timeout: Duration) -> ReplayTransport {
+        let mut records: Vec<Record> = Vec::new();
+
+        for line in recording.lines() {
+            let mut fields = line.splitn(3, ' ');
+            let (Some(kind), Some(at), bytes) = (fields.next(), fields.next(), fields.next()) else { continue };
+            let Ok(at) = at.parse() else { continue };
+            let bytes = unescape(bytes.unwrap_or(""));
+            match kind {
+                // A command is usually written in pieces, compare it up to its `\r`
+                ">" => match records.last_mut() {
+                    Some(Record::Sent { bytes: sent, .. }) if !sent.ends_with(b"\r") => sent.extend(bytes),
+                    _ => records.push(Record::Sent { at, bytes }),
+                },
+                "<" => records.push(Record::Received { at, bytes }),
+                _ => {}
+            }
+        }
+
+        let recorded_start = match records.first() {
+            Some(Record::Sent { at, .. } | Record::Received { at, .. }) => *at,
+            None => 0,
+        };
+        ReplayTransport {
+            records,
+            position: 0,
+            input: Vec::new(),
+            output: VecDeque::new(),
+            played: (Instant::now(), recorded_start),
+            read_timeout,
+        }
+    }
+
+    pub fn open(path: &str, read_timeout: Duration) -> Result<ReplayTransport, io::Error> {
+        Ok(ReplayTransport::parse(&fs::read_to_string(path)?, read_timeout))
+    }
+
+    /// Plays the received record at `position`, returns how long it is still due in
+    fn play_received(&mut self, wait: bool) -> Option<Duration> {
+        let Some(Record::Received { at, bytes }) = self.records.get(self.position) else { return None };
+        let (played_at, recorded_at) = self.played;
+        let due = played_at + Duration::from_millis(at.saturating_sub(recorded_at) as u64);
+        let now = Instant::now();
+        if wait && now < due {
+            return Some(due - now);
+        }
+        self.output.extend(bytes);
+        self.played = (now, *at);
+        self.position += 1;
+        None
+    }
+
+    /// Matches what the app wrote against the next recorded command
+    fn process_input(&mut self) -> io::Result<()> {
+        // Anything the modem sent before the command was recorded comes first
+        while matches!(self.records.get(self.position), Some(Record::Received { .. })) {
+            self.play_received(false);
+        }
+        let Some(&Record::Sent { at, bytes: ref sent }) = self.records.get(self.position) else {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of the recording"));
+        };
+        let length = sent.len().min(self.input.len());
+        if sent[..length] != self.input[..length] {
+            let expected = sent.escape_ascii().to_string();
+            let written = self.input.escape_ascii().to_string();
+            self.input.clear();
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("recording expects {}, not {}", expected, written)));
+        }
+        if length == sent.len() {
+            self.input.drain(..length);
+            self.position += 1;
+            // The answer is timed from when the app sent the command
+            self.played = (Instant::now(), at);
+        }
+        Ok(())
+    }
+}
+
+impl Read for ReplayTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output.is_empty() {
+            match self.records.get(self.position) {
+                None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of the recording")),
+                Some(Record::Sent { .. }) => {
+                    thread::sleep(self.read_timeout);
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+                Some(Record::Received { .. }) => {
+                    if let Some(wait) = self.play_received(true) {
+                        thread::sleep(wait.min(self.read_timeout));
+                        if self.play_received(true).is_some() {
+                            return Err(io::ErrorKind::TimedOut.into());
+                        }
+                    }
+                }
+            }
+        }
+        let count = buf.len().min(self.output.len());
+        for (slot, byte) in buf.iter_mut().zip(self.output.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for ReplayTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input.extend_from_slice(buf);
+        self.process_input()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for ReplayTransport {}
@@ -0,0 +1,292 @@
+use std::fmt;
+use serde::Serialize;
+use crate::modem_tools::urc::{Urc, UrcKind};
+
+/// Registration causes of 3GPP TS 24.008 (MM/GMM), 24.301 (EMM) and 24.501 (5GMM),
+/// which share their numbering for the common ones
+static REJECT_CAUSES: &[(u16, &str)] = &[
+    (2, "IMSI unknown in HLR"),
+    (3, "illegal MS"),
+    (5, "IMEI not accepted"),
+    (6, "illegal ME"),
+    (7, "services not allowed"),
+    (8, "packet and non-packet services not allowed"),
+    (9, "identity cannot be derived by the network"),
+    (10, "implicitly detached"),
+    (11, "PLMN not allowed"),
+    (12, "location area not allowed"),
+    (13, "roaming not allowed in this location area"),
+    (14, "packet services not allowed in this PLMN"),
+    (15, "no suitable cells in location area"),
+    (17, "network failure"),
+    (22, "congestion"),
+    (25, "not authorized for this CSG"),
+    (111, "protocol error, unspecified"),
+];
+
+/// `+CREG`, `+CGREG`, `+CEREG` and `+C5GREG` report one domain each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegistrationDomain {
+    /// Circuit switched, GSM/UMTS voice and SMS
+    Cs,
+    /// GPRS/UMTS packet switched
+    Ps,
+    /// LTE
+    Eps,
+    /// 5G core
+    Fivegs,
+}
+
+impl RegistrationDomain {
+    /// Most capable first
+    pub const ALL: [RegistrationDomain; 4] = [RegistrationDomain::Fivegs, RegistrationDomain::Eps, RegistrationDomain::Ps, RegistrationDomain::Cs];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            RegistrationDomain::Cs => "+CREG",
+            RegistrationDomain::Ps => "+CGREG",
+            RegistrationDomain::Eps => "+CEREG",
+            RegistrationDomain::Fivegs => "+C5GREG",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RegistrationDomain::Cs => "CS",
+            RegistrationDomain::Ps => "PS",
+            RegistrationDomain::Eps => "EPS",
+            RegistrationDomain::Fivegs => "5GS",
+        }
+    }
+
+    fn from_urc(kind: UrcKind) -> Option<RegistrationDomain> {
+        match kind {
+            UrcKind::CsRegistration => Some(RegistrationDomain::Cs),
+            UrcKind::PsRegistration => Some(RegistrationDomain::Ps),
+            UrcKind::EpsRegistration => Some(RegistrationDomain::Eps),
+            UrcKind::NrRegistration => Some(RegistrationDomain::Fivegs),
+            _ => None,
+        }
+    }
+
+    /// Field of `<cause_type>`, counting from `<stat>`: `+CGREG` has `<rac>` before it
+    /// and `+C5GREG` the allowed NSSAI
+    fn cause_field(self) -> usize {
+        match self {
+            RegistrationDomain::Cs | RegistrationDomain::Eps => 4,
+            RegistrationDomain::Ps => 5,
+            RegistrationDomain::Fivegs => 6,
+        }
+    }
+}
+
+/// `<stat>` of 3GPP TS 27.007 clauses 7.2, 10.1.20, 10.1.22 and 10.1.47
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStatus {
+    NotRegistered,
+    Searching,
+    Denied,
+    #[default]
+    Unknown,
+    Home,
+    Roaming,
+    SmsOnlyHome,
+    SmsOnlyRoaming,
+    /// Attached for emergency bearer services or restricted local operator services only
+    Limited,
+}
+
+impl RegistrationStatus {
+    fn from_stat(stat: &str) -> Option<RegistrationStatus> {
+        Some(match stat.parse::<u8>().ok()? {
+            0 => RegistrationStatus::NotRegistered,
+            // 9 and 10: registered, "CSFB not preferred"
+            1 | 9 => RegistrationStatus::Home,
+            2 => RegistrationStatus::Searching,
+            3 => RegistrationStatus::Denied,
+            5 | 10 => RegistrationStatus::Roaming,
+            6 => RegistrationStatus::SmsOnlyHome,
+            7 => RegistrationStatus::SmsOnlyRoaming,
+            8 | 11 => RegistrationStatus::Limited,
+            _ => RegistrationStatus::Unknown,
+        })
+    }
+
+    pub fn is_registered(self) -> bool {
+        matches!(self, RegistrationStatus::Home | RegistrationStatus::Roaming
+            | RegistrationStatus::SmsOnlyHome | RegistrationStatus::SmsOnlyRoaming)
+    }
+
+    /// How close to having service, to pick the domain that tells the most
+    fn rank(self) -> u8 {
+        match self {
+            RegistrationStatus::NotRegistered => 0,
+            RegistrationStatus::Unknown => 1,
+            RegistrationStatus::Denied => 2,
+            RegistrationStatus::Searching => 3,
+            RegistrationStatus::Limited => 4,
+            _ => 5,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            RegistrationStatus::NotRegistered => "not registered",
+            RegistrationStatus::Searching => "searching",
+            RegistrationStatus::Denied => "registration denied",
+            RegistrationStatus::Unknown => "unknown",
+            RegistrationStatus::Home => "registered, home network",
+            RegistrationStatus::Roaming => "registered, roaming",
+            RegistrationStatus::SmsOnlyHome => "registered for SMS only, home network",
+            RegistrationStatus::SmsOnlyRoaming => "registered for SMS only, roaming",
+            RegistrationStatus::Limited => "emergency services only",
+        }
+    }
+
+    /// Short form for the history columns
+    pub fn key(self) -> &'static str {
+        match self {
+            RegistrationStatus::NotRegistered => "not_registered",
+            RegistrationStatus::Searching => "searching",
+            RegistrationStatus::Denied => "denied",
+            RegistrationStatus::Unknown => "unknown",
+            RegistrationStatus::Home => "home",
+            RegistrationStatus::Roaming => "roaming",
+            RegistrationStatus::SmsOnlyHome => "sms_only_home",
+            RegistrationStatus::SmsOnlyRoaming => "sms_only_roaming",
+            RegistrationStatus::Limited => "limited",
+        }
+    }
+}
+
+/// Why the network rejected the last registration attempt
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RejectCause {
+    /// `<cause_type>` 1, the cause is the manufacturer's own
+    pub vendor_specific: bool,
+    pub cause: u16,
+}
+
+impl fmt::Display for RejectCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.vendor_specific {
+            return write!(f, "vendor cause {}", self.cause);
+        }
+        match REJECT_CAUSES.iter().find(|(cause, _)| *cause == self.cause) {
+            Some((_, description)) => write!(f, "cause {} ({})", self.cause, description),
+            None => write!(f, "cause {}", self.cause),
+        }
+    }
+}
+
+/// One domain as reported with `<n>` 2 or 3: status, location and reject cause
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Registration {
+    pub status: RegistrationStatus,
+    /// LAC for CS and PS, TAC for EPS and 5GS
+    pub area_code: Option<u32>,
+    pub cell_id: Option<u64>,
+    /// `<AcT>` as in `+COPS`
+    pub act: Option<u8>,
+    pub reject_cause: Option<RejectCause>,
+}
+
+impl Registration {
+    /// Answer to the read command, `<n>,<stat>[,...]`
+    pub fn from_response(domain: RegistrationDomain, line: &str) -> Option<Registration> {
+        let (_, fields) = line.strip_prefix(domain.prefix())?.strip_prefix(':')?.split_once(',')?;
+        Registration::from_fields(domain, fields)
+    }
+
+    /// URC, `<stat>[,...]` without `<n>`
+    pub fn from_urc(domain: RegistrationDomain, line: &str) -> Option<Registration> {
+        Registration::from_fields(domain, line.strip_prefix(domain.prefix())?.strip_prefix(':')?)
+    }
+
+    fn from_fields(domain: RegistrationDomain, fields: &str) -> Option<Registration> {
+        let fields: Vec<&str> = fields.split(',').map(|field| field.trim().trim_matches('"')).collect();
+        let field = |index: usize| fields.get(index).copied().filter(|field| !field.is_empty());
+        let status = RegistrationStatus::from_stat(field(0)?)?;
+        let cause_type = field(domain.cause_field());
+        let reject_cause = cause_type.zip(field(domain.cause_field() + 1))
+            .and_then(|(cause_type, cause)| Some(RejectCause { vendor_specific: cause_type == "1", cause: cause.parse().ok()? }));
+        Some(Registration {
+            status,
+            area_code: field(1).and_then(|area_code| u32::from_str_radix(area_code, 16).ok()),
+            cell_id: field(2).and_then(|cell_id| u64::from_str_radix(cell_id, 16).ok()),
+            act: field(3).and_then(|act| act.parse().ok()),
+            reject_cause,
+        })
+    }
+
+    /// `registered, roaming (EPS, TAC 4E91, CI 9C03)`
+    pub fn describe(&self, domain: RegistrationDomain) -> String {
+        let mut details = vec![domain.name().to_string()];
+        if let Some(area_code) = self.area_code {
+            let name = match domain {
+                RegistrationDomain::Cs | RegistrationDomain::Ps => "LAC",
+                RegistrationDomain::Eps | RegistrationDomain::Fivegs => "TAC",
+            };
+            details.push(format!("{} {:X}", name, area_code));
+        }
+        if let Some(cell_id) = self.cell_id {
+            details.push(format!("CI {:X}", cell_id));
+        }
+        if let Some(reject_cause) = self.reject_cause {
+            details.push(reject_cause.to_string());
+        }
+        format!("{} ({})", self.status.description(), details.join(", "))
+    }
+}
+
+/// Registration of every domain the modem reported, `None` for the others
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct NetworkRegistration {
+    pub cs: Option<Registration>,
+    pub ps: Option<Registration>,
+    pub eps: Option<Registration>,
+    #[serde(rename = "5gs")]
+    pub fivegs: Option<Registration>,
+}
+
+impl NetworkRegistration {
+    pub fn get(&self, domain: RegistrationDomain) -> Option<&Registration> {
+        match domain {
+            RegistrationDomain::Cs => self.cs.as_ref(),
+            RegistrationDomain::Ps => self.ps.as_ref(),
+            RegistrationDomain::Eps => self.eps.as_ref(),
+            RegistrationDomain::Fivegs => self.fivegs.as_ref(),
+        }
+    }
+
+    pub fn set(&mut self, domain: RegistrationDomain, registration: Registration) {
+        let slot = match domain {
+            RegistrationDomain::Cs => &mut self.cs,
+            RegistrationDomain::Ps => &mut self.ps,
+            RegistrationDomain::Eps => &mut self.eps,
+            RegistrationDomain::Fivegs => &mut self.fivegs,
+        };
+        *slot = Some(registration);
+    }
+
+    /// Applies a registration URC, `false` for any other URC
+    pub fn update(&mut self, urc: &Urc) -> bool {
+        let Some(domain) = RegistrationDomain::from_urc(urc.kind) else { return false };
+        match Registration::from_urc(domain, &urc.line) {
+            Some(registration) => {
+                self.set(domain, registration);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The domain closest to service, the most capable one on a tie: an LTE modem
+    /// reports EPS even when `+C5GREG` says not registered
+    pub fn current(&self) -> Option<(RegistrationDomain, &Registration)> {
+        RegistrationDomain::ALL.iter().rev()
+            .filter_map(|&domain| Some((domain, self.get(domain)?)))
+            .max_by_key(|(_, registration)| registration.status.rank())
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use crate::modem_tools::at_response::{AtError, AtResponse, ResponseReader};
+use crate::modem_tools::modem::{read_unsolicited, send_at_command};
+use crate::modem_tools::recording::{RecordingTransport, SessionRecorder};
+use crate::modem_tools::urc::{Urc, UrcDispatcher};
+use crate::serial_tools::transport::{Endpoint, Transport};
+
+/// How long the worker waits for a request before checking the port for URCs.
+const IDLE_POLL: Duration = Duration::from_millis(50);
+
+struct SessionRequest {
+    command: String,
+    reply: Sender<Result<AtResponse, AtError>>,
+}
+
+/// Long-lived connection to the modem AT port.
+///
+/// The transport is opened once and handed to a worker thread. Every clone of the
+/// session shares the same request queue, so the poller, the terminal and the
+/// band editor never write to the device at the same time. Between requests
+/// the worker keeps reading the port and publishes unsolicited result codes to
+/// subscribers. The port is closed when the last clone is dropped.
+#[derive(Clone)]
+pub struct ModemSession {
+    requests: Sender<SessionRequest>,
+    urcs: UrcDispatcher,
+    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+}
+
+impl ModemSession {
+    pub fn open(endpoint: &Endpoint, baud_rate: u32) -> Result<ModemSession, io::Error> {
+        Ok(ModemSession::start(endpoint.open(baud_rate, IDLE_POLL)?))
+    }
+
+    pub fn start(port: Box<dyn Transport>) -> ModemSession {
+        let (requests, queue) = mpsc::channel::<SessionRequest>();
+        let urcs = UrcDispatcher::default();
+        let recorder = Arc::new(Mutex::new(None));
+
+        let dispatcher = urcs.clone();
+        let port = RecordingTransport::new(port, recorder.clone());
+        thread::spawn(move || serve(port, queue, dispatcher));
+
+        ModemSession { requests, urcs, recorder }
+    }
+
+    /// Queue `command` and block until the modem answers it.
+    pub fn execute(&self, command: &str) -> Result<AtResponse, AtError> {
+        let (reply, response) = mpsc::channel();
+        self.requests
+            .send(SessionRequest { command: command.to_string(), reply })
+            .map_err(|_| AtError::SessionClosed)?;
+        response.recv().map_err(|_| AtError::SessionClosed)?
+    }
+
+    /// Receive every unsolicited result code from now on.
+    pub fn subscribe(&self) -> Receiver<Urc> {
+        self.urcs.subscribe()
+    }
+
+    /// Capture all bytes exchanged from now on into a session file for `replay://`.
+    pub fn record_to(&self, path: &str) -> Result<(), io::Error> {
+        *self.recorder.lock().unwrap() = Some(SessionRecorder::create(path)?);
+        Ok(())
+    }
+}
+
+fn serve(mut port: RecordingTransport, queue: Receiver<SessionRequest>, urcs: UrcDispatcher) {
+    let mut reader = ResponseReader::default();
+
+    loop {
+        match queue.recv_timeout(IDLE_POLL) {
+            Ok(request) => {
+                let response = send_at_command(&mut port, &mut reader, &request.command);
+                // The caller may have given up waiting, nothing to do then
+                let _ = request.reply.send(response);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // A broken port shows up as an error on the next request
+                let _ = read_unsolicited(&mut port, &mut reader);
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        for urc in reader.take_unsolicited() {
+            urcs.publish(urc);
+        }
+    }
+}
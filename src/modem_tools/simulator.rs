@@ -0,0 +1,487 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::Duration;
+use crate::modem_tools::converters::lte_channel;
+use crate::serial_tools::transport::Transport;
+
+/// What the simulated modem does when it receives a command.
+#[derive(Debug, Clone, PartialEq)]
+enum Fault {
+    Error(Option<u16>),
+    Timeout,
+    /// Answered only when the next command arrives, after the host gave up waiting
+    Late,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Set(String, i32),
+    Drift(String, i32),
+    Handover { pci: i32, earfcn: i32 },
+    Fault(String, Fault),
+    Reply(String, String),
+    Urc(String),
+}
+
+/// Scriptable behaviour of the simulated modem.
+///
+/// One directive per line, `#` starts a comment. A directive may be prefixed
+/// with `at <cycle>` to run it once the host has polled signal quality
+/// (`+CSQ`) that many times; without a prefix it applies from the start.
+///
+/// ```text
+/// drift rsrp 2                 # random walk, up to ±2 dB per cycle
+/// at 3 handover 0x0063 6300    # new serving cell PCI and EARFCN
+/// at 5 error AT+XACT? 10       # next AT+XACT? fails with +CME ERROR: 10
+/// at 6 timeout AT+CSQ?         # next AT+CSQ? is never answered
+/// late AT+COPS?                # next AT+COPS? is answered just before the command after it
+/// at 7 urc +CEREG: 1,"4E91","00009C03",7
+/// reply AT+CGMR +CGMR: "18500.5001.00.05.27.30"
+/// set sinr 12
+/// at 8 set reg 3               # registration denied...
+/// at 8 set cause 11            # ...with reject cause 11
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    steps: Vec<(u32, Action)>,
+}
+
+impl Scenario {
+    pub fn parse(script: &str) -> Result<Scenario, String> {
+        let mut steps = Vec::new();
+
+        for (number, line) in script.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("scenario line {}: {}", number + 1, message);
+
+            let (cycle, directive) = match line.strip_prefix("at ") {
+                Some(rest) => {
+                    let (cycle, directive) = rest.trim().split_once(' ').ok_or_else(|| error("missing directive"))?;
+                    (cycle.parse::<u32>().map_err(|_| error("bad cycle number"))?, directive.trim())
+                }
+                None => (0, line),
+            };
+
+            let (name, args) = directive.split_once(' ').unwrap_or((directive, ""));
+            let args = args.trim();
+            let words: Vec<&str> = args.split_whitespace().collect();
+            let number_arg = |i: usize| -> Result<i32, String> {
+                words.get(i).and_then(|w| parse_number(w)).ok_or_else(|| error("expected a number"))
+            };
+
+            let action = match name {
+                "set" => Action::Set(words.first().ok_or_else(|| error("missing field"))?.to_string(), number_arg(1)?),
+                "drift" => Action::Drift(words.first().ok_or_else(|| error("missing field"))?.to_string(), number_arg(1)?),
+                "handover" => Action::Handover { pci: number_arg(0)?, earfcn: number_arg(1)? },
+                "error" => {
+                    let command = words.first().ok_or_else(|| error("missing command"))?;
+                    let code = words.get(1).map(|w| w.parse::<u16>().map_err(|_| error("bad error code"))).transpose()?;
+                    Action::Fault(command_key(command), Fault::Error(code))
+                }
+                "timeout" => Action::Fault(command_key(words.first().ok_or_else(|| error("missing command"))?), Fault::Timeout),
+                "late" => Action::Fault(command_key(words.first().ok_or_else(|| error("missing command"))?), Fault::Late),
+                "reply" => {
+                    let (command, reply) = args.split_once(' ').ok_or_else(|| error("missing reply"))?;
+                    Action::Reply(command_key(command), reply.trim().to_string())
+                }
+                "urc" => Action::Urc(args.to_string()),
+                _ => return Err(error(&format!("unknown directive '{}'", name))),
+            };
+            steps.push((cycle, action));
+        }
+
+        Ok(Scenario { steps })
+    }
+
+    pub fn load(path: &str) -> Result<Scenario, io::Error> {
+        let script = fs::read_to_string(path)?;
+        Scenario::parse(&script).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+fn parse_number(word: &str) -> Option<i32> {
+    match word.strip_prefix("0x") {
+        Some(hex) => i32::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+/// `AT+CSQ?` and `+csq` both become `+CSQ`; compound command parts arrive without the `AT`.
+fn command_key(command: &str) -> String {
+    let command = command.trim().to_ascii_uppercase();
+    let command = command.strip_prefix("AT").unwrap_or(&command);
+    command.trim_end_matches('?').trim().to_string()
+}
+
+/// Domains the L850-GL reports, it has no 5G
+const REGISTRATION_PREFIXES: [&str; 3] = ["+CREG", "+CGREG", "+CEREG"];
+
+/// Radio and identity state of the simulated modem. Signal values are kept in
+/// dBm/dB and encoded the way the real firmware reports them.
+#[derive(Debug, Clone)]
+struct ModemState {
+    rsrp: i32,
+    rsrq: i32,
+    sinr: i32,
+    csq: i32,
+    pci: i32,
+    earfcn: i32,
+    bandwidth: i32,
+    timing_advance: i32,
+    /// Registration `<stat>` and reject cause, the same in every domain
+    registration: i32,
+    reject_cause: i32,
+    /// `<n>` of `+CREG`, `+CGREG` and `+CEREG`
+    registration_reports: [i32; 3],
+    neighbours: Vec<(i32, i32, i32)>,
+    umts_bands: Vec<usize>,
+    lte_bands: Vec<usize>,
+}
+
+impl Default for ModemState {
+    fn default() -> Self {
+        // Same cell as the fixture in src/tests.rs
+        ModemState {
+            rsrp: -92,
+            rsrq: -12,
+            sinr: -2,
+            csq: 11,
+            pci: 0x62,
+            earfcn: 1500,
+            bandwidth: 5,
+            timing_advance: 3,
+            registration: 1,
+            reject_cause: 0,
+            registration_reports: [0, 0, 2],
+            neighbours: vec![(0x61, -99, -19), (0x6A, -98, -16)],
+            umts_bands: vec![1, 2, 4, 5, 8],
+            lte_bands: vec![1, 2, 3, 4, 5, 7, 8, 12, 13, 17, 18, 19, 20, 26, 28, 29, 30, 41, 66],
+        }
+    }
+}
+
+impl ModemState {
+    fn field(&mut self, name: &str) -> Option<(&mut i32, i32, i32)> {
+        match name {
+            "rsrp" => Some((&mut self.rsrp, -140, -44)),
+            "rsrq" => Some((&mut self.rsrq, -20, -3)),
+            "sinr" => Some((&mut self.sinr, -20, 30)),
+            "csq" => Some((&mut self.csq, 0, 31)),
+            "pci" => Some((&mut self.pci, 0, 503)),
+            "earfcn" => Some((&mut self.earfcn, 0, 70_000)),
+            "bw" => Some((&mut self.bandwidth, 0, 5)),
+            "ta" => Some((&mut self.timing_advance, 0, 1282)),
+            "reg" => Some((&mut self.registration, 0, 10)),
+            "cause" => Some((&mut self.reject_cause, 0, 255)),
+            _ => None,
+        }
+    }
+
+    /// `earfcns` are DL and UL channels, `measurements` the raw RSRP, RSRQ and SINR indexes.
+    fn xmci_line(cell: &str, pci: i32, earfcns: (i32, i32), measurements: (i32, i32, i32), ta: &str) -> String {
+        let (rsrp, rsrq, sinr) = measurements;
+        format!(
+            "+XMCI: {},\"0x{:04X}\",\"0x{:08X}\",\"0x{:08X}\",\"0xFFFFFFFF\",{},{},{},\"{}\",\"0x00000000\"",
+            cell, pci, earfcns.0, earfcns.1, rsrp, rsrq, sinr, ta
+        )
+    }
+
+    /// Answer to `+CREG?`, `+CGREG?` or `+CEREG?` with the fields `<n>` asks for
+    fn registration_line(&self, domain: usize) -> String {
+        let prefix = REGISTRATION_PREFIXES[domain];
+        let reports = self.registration_reports[domain];
+        let mut line = format!("{}: {},{}", prefix, reports, self.registration);
+        if reports >= 2 {
+            line.push_str(",\"4E91\",\"00009C03\",7");
+        }
+        // Denied: <cause_type>,<reject_cause>, after <rac> for +CGREG
+        if reports >= 3 && self.registration == 3 {
+            if prefix == "+CGREG" {
+                line.push_str(",\"FF\"");
+            }
+            line.push_str(&format!(",0,{}", self.reject_cause));
+        }
+        line
+    }
+
+    /// `+CREG=<n>` and the like, `None` for any other command
+    fn set_registration_reports(&mut self, key: &str) -> Option<bool> {
+        let (prefix, reports) = key.split_once('=')?;
+        let domain = REGISTRATION_PREFIXES.iter().position(|&registration| registration == prefix)?;
+        match reports.parse::<i32>() {
+            Ok(reports) if (0..=3).contains(&reports) => {
+                self.registration_reports[domain] = reports;
+                Some(true)
+            }
+            _ => Some(false),
+        }
+    }
+
+    fn answer(&self, key: &str) -> Option<Vec<String>> {
+        let lines = match key {
+            "" | "E0" | "E1" | "+CMEE=1" | "+CMEE=2" => vec![],
+            "I" => vec![
+                "Manufacturer: Fibocom Wireless Inc.".to_string(),
+                "Model: L850-GL".to_string(),
+                "Revision: 18500.5001.00.05.27.30".to_string(),
+            ],
+            "+CGMI" => vec!["+CGMI: \"Fibocom\"".to_string()],
+            "+CGMM" => vec!["+CGMM: \"L850 LTE Module\"".to_string()],
+            "+FMM" => vec!["+FMM: \"L850 LTE Module\",\"L850\"".to_string()],
+            "+GTPKGVER" => vec!["+GTPKGVER: \"18500.5001.00.05.27.30_5001.05.001.035\"".to_string()],
+            "+CFSN" => vec!["+CFSN: \"D1M2LG1EA3\"".to_string()],
+            "+CGSN" => vec!["+CGSN: \"015550006919978\"".to_string()],
+            "+CIMI" => vec!["+CIMI: 220033400995562".to_string()],
+            "+CCID" => vec!["+CCID: 89381030000328789401".to_string()],
+            "+COPS" => vec!["+COPS: 0,0,\"mt:s\",7".to_string()],
+            "+CGCONTRDP=1" => vec!["+CGCONTRDP: 1,6,\"3gnet.mnc003.mcc220.gprs\",\"10.179.248.170.255.0.0.0\",\"10.179.248.171\",\"172.22.23.175\",\"172.21.8.175\",\"\",\"\",0".to_string()],
+            "+CSQ" => vec![format!("+CSQ: {},2", self.csq)],
+            "+CGMR" => vec!["+CGMR: \"18500.5001.00.05.27.30\"".to_string()],
+            "+CESQ" => vec![format!("+CESQ: 99,99,255,255,{},{}", (self.rsrq + 20) * 2, self.rsrp + 141)],
+            "+CREG" => vec![self.registration_line(0)],
+            "+CGREG" => vec![self.registration_line(1)],
+            "+CEREG" => vec![self.registration_line(2)],
+            "+CGDCONT" => vec!["+CGDCONT: 1,\"IP\",\"3gnet\",\"0.0.0.0\",0,0".to_string()],
+            "+CGPADDR" => vec!["+CGPADDR: 1,\"10.179.248.170\"".to_string()],
+            "+XCCINFO" => vec!["+XCCINFO: 0,220,03,\"00009C03\",3,103,\"FFFF\",1,\"FF\",\"4E91\",0,0,0,0,0,0,0,0".to_string()],
+            "+XLEC" => vec![format!("+XLEC: 0,1,{},BAND_LTE_{}", self.bandwidth, lte_channel(self.earfcn).map_or(0, |channel| channel.band))],
+            "+XMCI=1" => {
+                let mut lines = vec![ModemState::xmci_line(
+                    "4,220,03,\"0x4E91\",\"0x00009C03\"", self.pci, (self.earfcn, self.earfcn + 18000),
+                    (self.rsrp + 141, (self.rsrq + 20) * 2, self.sinr * 2),
+                    &format!("0x{:08X}", self.timing_advance),
+                )];
+                for &(pci, rsrp, rsrq) in &self.neighbours {
+                    // Neighbours report no SINR and no timing advance
+                    lines.push(ModemState::xmci_line(
+                        "5,000,000,\"0xFFFE\",\"0xFFFFFFFF\"", pci, (self.earfcn, -1),
+                        (rsrp + 141, (rsrq + 20) * 2, 255), "0x7FFFFFFF",
+                    ));
+                }
+                lines
+            }
+            // Test form, `?` is stripped from the key. Everything is enabled out of the box.
+            "+XACT=" => {
+                let supported = ModemState::default();
+                let bands: Vec<String> = supported.umts_bands.iter().copied()
+                    .chain(supported.lte_bands.iter().map(|b| b + 100))
+                    .map(|b| b.to_string())
+                    .collect();
+                vec![format!("+XACT: (0-6),(0-2),0,{}", bands.join(","))]
+            }
+            "+XACT" => {
+                let umts: Vec<String> = self.umts_bands.iter().map(|b| b.to_string()).collect();
+                let lte: Vec<String> = self.lte_bands.iter().map(|b| (b + 100).to_string()).collect();
+                let xact = match (umts.is_empty(), lte.is_empty()) {
+                    (false, false) => format!("+XACT: 4,2,1,{},{}", umts.join(","), lte.join(",")),
+                    (true, false) => format!("+XACT: 2,1,,{}", lte.join(",")),
+                    (false, true) => format!("+XACT: 4,1,{},", umts.join(",")),
+                    (true, true) => "+XACT: 0,0,,0".to_string(),
+                };
+                vec![xact]
+            }
+            _ => return None,
+        };
+        Some(lines)
+    }
+
+    /// `+XACT=<mode>,<pref>,,<band>[,<band>...]`, as built by `save_bands_command`.
+    /// Band 0 enables every supported band.
+    fn set_bands(&mut self, args: &str) -> bool {
+        let fields: Vec<&str> = args.split(',').collect();
+        if fields.len() < 3 {
+            return false;
+        }
+        let bands: Vec<usize> = fields[3..].iter().filter_map(|b| b.trim().parse().ok()).collect();
+        if bands.contains(&0) {
+            let supported = ModemState::default();
+            self.umts_bands = supported.umts_bands;
+            self.lte_bands = supported.lte_bands;
+            return true;
+        }
+        self.umts_bands = bands.iter().copied().filter(|&b| b < 100 && b > 0).collect();
+        self.lte_bands = bands.iter().filter(|&&b| b > 100).map(|b| b - 100).collect();
+        true
+    }
+}
+
+/// Fibocom L850-GL look-alike answering over an in-process `Transport`.
+pub struct SimulatedModem {
+    scenario: Scenario,
+    state: ModemState,
+    faults: Vec<(String, Fault)>,
+    replies: Vec<(String, String)>,
+    cycle: u32,
+    seed: u64,
+    input: Vec<u8>,
+    output: VecDeque<u8>,
+    /// Response to a `late` command, sent along with the next one
+    held: Vec<u8>,
+    read_timeout: Duration,
+}
+
+impl SimulatedModem {
+    pub fn new(scenario: Scenario, read_timeout: Duration) -> SimulatedModem {
+        let mut modem = SimulatedModem {
+            scenario,
+            state: ModemState::default(),
+            faults: Vec::new(),
+            replies: Vec::new(),
+            cycle: 0,
+            seed: 0x2545_F491_4F6C_DD1D,
+            input: Vec::new(),
+            output: VecDeque::new(),
+            held: Vec::new(),
+            read_timeout,
+        };
+        modem.run_cycle();
+        modem
+    }
+
+    fn random(&mut self, amplitude: i32) -> i32 {
+        // xorshift keeps scenarios reproducible run to run
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed % (2 * amplitude as u64 + 1)) as i32 - amplitude
+    }
+
+    fn run_cycle(&mut self) {
+        let steps = self.scenario.steps.clone();
+        for (cycle, action) in steps {
+            match action {
+                Action::Drift(name, amplitude) => {
+                    if cycle <= self.cycle && amplitude > 0 {
+                        let step = self.random(amplitude);
+                        if let Some((value, min, max)) = self.state.field(&name) {
+                            *value = (*value + step).clamp(min, max);
+                        }
+                    }
+                }
+                _ if cycle != self.cycle => {}
+                Action::Set(name, new_value) => {
+                    if let Some((value, min, max)) = self.state.field(&name) {
+                        *value = new_value.clamp(min, max);
+                    }
+                }
+                Action::Handover { pci, earfcn } => {
+                    self.state.pci = pci;
+                    self.state.earfcn = earfcn;
+                }
+                Action::Fault(command, fault) => self.faults.push((command, fault)),
+                Action::Reply(command, reply) => self.replies.push((command, reply)),
+                Action::Urc(line) => self.output.extend(format!("\r\n{}\r\n", line).as_bytes()),
+            }
+        }
+    }
+
+    fn process(&mut self, command: &str) {
+        let held = std::mem::take(&mut self.held);
+        self.output.extend(held);
+        let mut response = format!("{}\r", command);
+        let mut lines = Vec::new();
+        let mut result = "OK".to_string();
+        let mut late = false;
+
+        for part in command.split(';') {
+            let key = command_key(part);
+
+            if key.contains("+CSQ") {
+                self.cycle += 1;
+                self.run_cycle();
+            }
+
+            if let Some(index) = self.faults.iter().position(|(command, _)| *command == key) {
+                match self.faults.remove(index).1 {
+                    Fault::Timeout => return,
+                    Fault::Late => late = true,
+                    Fault::Error(code) => {
+                        result = code.map_or("ERROR".to_string(), |code| format!("+CME ERROR: {}", code));
+                        break;
+                    }
+                }
+            }
+
+            if let Some((_, reply)) = self.replies.iter().find(|(command, _)| *command == key) {
+                lines.push(reply.clone());
+                continue;
+            }
+
+            if let Some(accepted) = self.state.set_registration_reports(&key) {
+                if !accepted {
+                    result = "ERROR".to_string();
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(args) = key.strip_prefix("+XACT=").filter(|args| !args.is_empty()) {
+                if !self.state.set_bands(args) {
+                    result = "ERROR".to_string();
+                    break;
+                }
+                continue;
+            }
+
+            match self.state.answer(&key) {
+                Some(answer) => lines.extend(answer),
+                None => {
+                    result = "ERROR".to_string();
+                    break;
+                }
+            }
+        }
+
+        for line in lines {
+            response.push_str(&format!("\r\n{}\r\n", line));
+        }
+        response.push_str(&format!("\r\n{}\r\n", result));
+        if late {
+            self.held = response.into_bytes();
+        } else {
+            self.output.extend(response.as_bytes());
+        }
+    }
+}
+
+impl Read for SimulatedModem {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output.is_empty() {
+            thread::sleep(self.read_timeout);
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let count = buf.len().min(self.output.len());
+        for (slot, byte) in buf.iter_mut().zip(self.output.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for SimulatedModem {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if byte == b'\r' {
+                let command = String::from_utf8_lossy(&self.input).trim().to_string();
+                self.input.clear();
+                if !command.is_empty() {
+                    self.process(&command);
+                }
+            } else {
+                self.input.push(byte);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for SimulatedModem {}
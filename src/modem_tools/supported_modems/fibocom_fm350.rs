@@ -0,0 +1,108 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::modem::get_modem_info;
+use crate::modem_tools::supported_modems::{parse_fibocom_band_list, BandCapabilities, ModemProfile};
+use crate::modem_tools::types::ModemInfo;
+
+// Fibocom encodes bands as <band> for UMTS, 100 + <band> for LTE and 5000 + <band> for NR
+// in +GTACT, +GTCCINFO and +GTCAINFO
+pub(crate) const LTE_BAND_OFFSET: usize = 100;
+pub(crate) const NR_BAND_OFFSET: usize = 5000;
+
+/// `+GTACT` RAT selection, (UMTS, LTE, NR) -> value
+pub(crate) const GTACT_RATS: [((bool, bool, bool), u8); 7] = [
+    ((true, false, false), 1),
+    ((false, true, false), 2),
+    ((true, true, false), 4),
+    ((false, false, true), 14),
+    ((true, false, true), 16),
+    ((false, true, true), 17),
+    ((true, true, true), 20),
+];
+
+/// Fibocom FM350-GL, MediaTek T700 based 5G modem. Serving and neighbour cells come
+/// from `+GTCCINFO`, carrier aggregation from `+GTCAINFO`, bands from `+GTACT`.
+pub struct FibocomFm350;
+
+pub static FIBOCOM_FM350GL: FibocomFm350 = FibocomFm350;
+
+static IDENTIFICATION: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\+FMM: .*"FM350"#).unwrap());
+
+impl ModemProfile for FibocomFm350 {
+    fn name(&self) -> &'static str {
+        "Fibocom FM350-GL"
+    }
+
+    fn identification(&self) -> &Regex {
+        &IDENTIFICATION
+    }
+
+    fn supported_umts_bands(&self) -> &'static [usize] {
+        &[1, 2, 4, 5, 8]
+    }
+
+    fn supported_lte_bands(&self) -> &'static [usize] {
+        &[
+            1, 2, 3, 4, 5, 7, 8, 12, 13, 14, 17, 18, 19, 20, 25, 26, 28, 29, 30, 32, 66, 71,
+            34, 38, 39, 40, 41, 42, 43, 48,
+        ]
+    }
+
+    fn lte_4x4_mimo_bands(&self) -> &'static [usize] {
+        &[1, 2, 3, 4, 7, 25, 30, 32, 34, 38, 39, 40, 41, 42, 43, 48, 66]
+    }
+
+    fn supported_nr_bands(&self) -> &'static [usize] {
+        &[1, 2, 3, 5, 7, 8, 12, 13, 14, 18, 20, 25, 26, 28, 29, 30, 38, 40, 41, 48, 66, 70, 71, 77, 78, 79]
+    }
+
+    fn band_query(&self) -> Option<&'static str> {
+        Some("AT+GTACT=?")
+    }
+
+    fn parse_supported_bands(&self, response: &str) -> Option<BandCapabilities> {
+        parse_fibocom_band_list(response, "+GTACT:")
+    }
+
+    fn info_commands(&self) -> &'static [&'static str] {
+        &[
+            "AT+CGMI?",
+            "AT+FMM?",
+            "AT+GTPKGVER?",
+            "AT+CFSN?",
+            "AT+CGSN?",
+            "AT+CIMI?",
+            "AT+CCID?",
+            "AT+COPS?",
+            "AT+CGCONTRDP=1",
+            "AT+CSQ?",
+            "AT+CESQ",
+            "AT+CREG?",
+            "AT+CGREG?",
+            "AT+CEREG?",
+            "AT+C5GREG?",
+            "AT+GTCCINFO?",
+            "AT+GTCAINFO?",
+            "AT+GTACT?",
+        ]
+    }
+
+    fn parse_info(&self, info_string: String) -> ModemInfo {
+        // +GTCCINFO, +GTCAINFO and +GTACT have their parsers next to the ones of the LTE modules
+        get_modem_info(info_string)
+    }
+
+    fn band_lock_command(&self, umts_bands: &[usize], lte_bands: &[usize], nr_bands: &[usize]) -> Option<String> {
+        let selected = (!umts_bands.is_empty(), !lte_bands.is_empty(), !nr_bands.is_empty());
+        let Some(&(_, rat)) = GTACT_RATS.iter().find(|&&(rats, _)| rats == selected) else {
+            // Nothing selected: all RATs, all bands
+            return Some("AT+GTACT=20".to_string());
+        };
+        let bands: Vec<String> = umts_bands.iter().copied()
+            .chain(lte_bands.iter().map(|band| band + LTE_BAND_OFFSET))
+            .chain(nr_bands.iter().map(|band| band + NR_BAND_OFFSET))
+            .map(|band| band.to_string())
+            .collect();
+        Some(format!("AT+GTACT={},,,{}", rat, bands.join(",")))
+    }
+}
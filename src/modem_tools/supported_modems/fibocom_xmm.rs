@@ -0,0 +1,96 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::modem::{get_modem_info, save_bands_command};
+use crate::modem_tools::supported_modems::{parse_fibocom_band_list, BandCapabilities, ModemProfile};
+use crate::modem_tools::types::ModemInfo;
+
+/// Fibocom modem built on an Intel XMM chipset (L850-GL: XMM7360, L860-GL: XMM7560).
+/// Signal comes from `+XMCI`, carrier aggregation from `+XLEC`, bands from `+XACT`;
+/// the models only differ in what they support.
+pub struct FibocomXmm {
+    name: &'static str,
+    identification: Lazy<Regex>,
+    supported_umts_bands: &'static [usize],
+    supported_lte_bands: &'static [usize],
+    lte_4x4_mimo_bands: &'static [usize],
+}
+
+/// Cat 9, 3CA, 2x2 MIMO
+pub static FIBOCOM_L850GL: FibocomXmm = FibocomXmm {
+    name: "Fibocom L850-GL",
+    identification: Lazy::new(|| Regex::new(r#"\+FMM: .*"L850"#).unwrap()),
+    supported_umts_bands: &[1, 2, 4, 5, 8],
+    supported_lte_bands: &[1, 2, 3, 4, 5, 7, 8, 12, 13, 17, 18, 19, 20, 26, 28, 29, 30, 41, 66],
+    lte_4x4_mimo_bands: &[],
+};
+
+/// Cat 16, 5CA, 4x4 MIMO on the mid and high bands, adds band 14, 25, 32 and the TDD bands
+pub static FIBOCOM_L860GL: FibocomXmm = FibocomXmm {
+    name: "Fibocom L860-GL",
+    identification: Lazy::new(|| Regex::new(r#"\+FMM: .*"L860"#).unwrap()),
+    supported_umts_bands: &[1, 2, 4, 5, 8],
+    supported_lte_bands: &[
+        1, 2, 3, 4, 5, 7, 8, 11, 12, 13, 14, 17, 18, 19, 20, 21, 25, 26, 28, 29, 30, 32, 66,
+        38, 39, 40, 41, 42, 43, 46, 48,
+    ],
+    lte_4x4_mimo_bands: &[1, 2, 3, 4, 7, 25, 30, 38, 40, 41, 66],
+};
+
+impl ModemProfile for FibocomXmm {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn identification(&self) -> &Regex {
+        &self.identification
+    }
+
+    fn supported_umts_bands(&self) -> &'static [usize] {
+        self.supported_umts_bands
+    }
+
+    fn supported_lte_bands(&self) -> &'static [usize] {
+        self.supported_lte_bands
+    }
+
+    fn lte_4x4_mimo_bands(&self) -> &'static [usize] {
+        self.lte_4x4_mimo_bands
+    }
+
+    fn band_query(&self) -> Option<&'static str> {
+        Some("AT+XACT=?")
+    }
+
+    fn parse_supported_bands(&self, response: &str) -> Option<BandCapabilities> {
+        parse_fibocom_band_list(response, "+XACT:")
+    }
+
+    fn info_commands(&self) -> &'static [&'static str] {
+        &[
+            "AT+CGMI?",
+            "AT+FMM?",
+            "AT+GTPKGVER?",
+            "AT+CFSN?",
+            "AT+CGSN?",
+            "AT+CIMI?",
+            "AT+CCID?",
+            "AT+COPS?",
+            "AT+CGCONTRDP=1",
+            "AT+CSQ?",
+            "AT+CESQ",
+            "AT+CREG?",
+            "AT+CGREG?",
+            "AT+CEREG?",
+            "AT+XCCINFO?; +XLEC?; +XMCI=1",
+            "AT+XACT?",
+        ]
+    }
+
+    fn parse_info(&self, info_string: String) -> ModemInfo {
+        get_modem_info(info_string)
+    }
+
+    fn band_lock_command(&self, umts_bands: &[usize], lte_bands: &[usize], _nr_bands: &[usize]) -> Option<String> {
+        Some(save_bands_command(umts_bands.to_vec(), lte_bands.to_vec()))
+    }
+}
@@ -0,0 +1,102 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::modem::get_modem_info;
+use crate::modem_tools::registration::{NetworkRegistration, Registration, RegistrationDomain};
+use crate::modem_tools::supported_modems::ModemProfile;
+use crate::modem_tools::types::ModemInfo;
+
+const INFO_COMMANDS: [&str; 14] = [
+    "AT+CGMI",
+    "AT+CGMM",
+    "AT+CGMR",
+    "AT+CGSN",
+    "AT+CIMI",
+    "AT+COPS?",
+    "AT+CSQ",
+    "AT+CESQ",
+    "AT+CREG?",
+    "AT+CGREG?",
+    "AT+CEREG?",
+    "AT+C5GREG?",
+    "AT+CGDCONT?",
+    "AT+CGPADDR",
+];
+
+/// Any modem following 3GPP TS 27.007: Quectel, Sierra Wireless, SIMCom, Telit...
+/// Only standard commands are used, so there is no carrier aggregation,
+/// neighbour cell or band information and no band lock.
+///
+/// Used when no vendor profile matches, it is not part of `PROFILES`.
+pub struct Generic3gpp;
+
+pub static GENERIC_3GPP: Generic3gpp = Generic3gpp;
+
+static IDENTIFICATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"").unwrap());
+
+/// Access technology of the most capable registered domain, for modems that
+/// leave it out of `+COPS?`
+fn registration_mode(registration: &NetworkRegistration) -> &'static str {
+    let registered: Vec<&Registration> = RegistrationDomain::ALL.iter()
+        .filter_map(|&domain| registration.get(domain))
+        .filter(|registration| registration.status.is_registered())
+        .collect();
+    if registered.is_empty() {
+        return "Not registered";
+    }
+    registered.iter()
+        .find_map(|registration| match registration.act? {
+            0 | 1 | 3 | 8 => Some("GSM"),
+            2 | 4 | 5 | 6 => Some("UMTS"),
+            // 10: E-UTRA connected to the 5G core
+            7 | 9 | 10 => Some("LTE"),
+            11 | 12 => Some("NR"),
+            13 => Some("EN-DC"),
+            _ => None,
+        })
+        .unwrap_or("Registered")
+}
+
+impl ModemProfile for Generic3gpp {
+    fn name(&self) -> &'static str {
+        "Generic 3GPP"
+    }
+
+    /// Matches any modem
+    fn identification(&self) -> &Regex {
+        &IDENTIFICATION
+    }
+
+    fn supported_umts_bands(&self) -> &'static [usize] {
+        &[]
+    }
+
+    fn supported_lte_bands(&self) -> &'static [usize] {
+        &[]
+    }
+
+    fn info_commands(&self) -> &'static [&'static str] {
+        &INFO_COMMANDS
+    }
+
+    /// LTE-only modems do not know +C5GREG
+    fn optional_commands(&self) -> &'static [&'static str] {
+        &["AT+C5GREG?"]
+    }
+
+    fn parse_info(&self, info_string: String) -> ModemInfo {
+        // Every response is standard and read by the shared parsers
+        let mut signal_info = get_modem_info(info_string);
+
+        // TS 27.007 clause 8.5: -113 dBm + 2 dB per step, 99 is not known
+        signal_info.rssi = signal_info.csq.filter(|csq| (0..=31).contains(csq)).map(|csq| csq * 2 - 113);
+        if signal_info.mode.is_none() {
+            signal_info.mode = Some(registration_mode(&signal_info.registration).to_string());
+        }
+        signal_info
+    }
+
+    fn band_lock_command(&self, _umts_bands: &[usize], _lte_bands: &[usize], _nr_bands: &[usize]) -> Option<String> {
+        // Band selection is vendor specific
+        None
+    }
+}
@@ -0,0 +1,240 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use crate::modem_tools::at_response::AtError;
+use crate::modem_tools::registration::RegistrationDomain;
+use crate::modem_tools::session::ModemSession;
+use crate::modem_tools::types::ModemInfo;
+
+mod fibocom_fm350;
+mod fibocom_xmm;
+mod generic;
+
+pub use fibocom_fm350::FIBOCOM_FM350GL;
+pub(crate) use fibocom_fm350::{GTACT_RATS, LTE_BAND_OFFSET, NR_BAND_OFFSET};
+pub use fibocom_xmm::{FIBOCOM_L850GL, FIBOCOM_L860GL};
+pub use generic::GENERIC_3GPP;
+
+/// Everything model specific: how to recognise the modem, what it supports,
+/// which commands report its state and how to read them back.
+///
+/// A new model is supported by implementing this trait and adding it to `PROFILES`.
+pub trait ModemProfile: Sync {
+    /// Shown in the interface header, e.g. "Fibocom L850-GL"
+    fn name(&self) -> &'static str;
+
+    /// Matched against the responses of the `IDENTIFICATION_PROBES`
+    fn identification(&self) -> &Regex;
+
+    fn supported_umts_bands(&self) -> &'static [usize];
+    fn supported_lte_bands(&self) -> &'static [usize];
+
+    /// LTE bands received with four antennas
+    fn lte_4x4_mimo_bands(&self) -> &'static [usize] {
+        &[]
+    }
+
+    fn supported_nr_bands(&self) -> &'static [usize] {
+        &[]
+    }
+
+    /// Command listing the bands this particular unit supports, regional SKUs
+    /// of the same model differ. The declared band lists are used without one.
+    fn band_query(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Reads the `band_query` response, `None` if it cannot be understood
+    fn parse_supported_bands(&self, _response: &str) -> Option<BandCapabilities> {
+        None
+    }
+
+    /// Commands sent on every poll, their responses are concatenated for `parse_info`
+    fn info_commands(&self) -> &'static [&'static str];
+
+    /// Info commands some units of the profile do not know: an error or no answer at
+    /// all is not reported
+    fn optional_commands(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Never fails: what could not be read is left `None` and listed in `ModemInfo::parse_report`
+    fn parse_info(&self, info_string: String) -> ModemInfo;
+
+    /// Command enabling exactly the given bands, `None` if the modem cannot lock bands
+    fn band_lock_command(&self, umts_bands: &[usize], lte_bands: &[usize], nr_bands: &[usize]) -> Option<String>;
+}
+
+pub static PROFILES: [&dyn ModemProfile; 3] = [&FIBOCOM_L850GL, &FIBOCOM_L860GL, &FIBOCOM_FM350GL];
+
+/// Sent once on connect. Vendor commands answer ERROR on other modems, which is fine.
+pub const IDENTIFICATION_PROBES: [&str; 5] = ["ATI", "AT+CGMI", "AT+CGMM", "AT+FMM?", "AT+GTPKGVER?"];
+
+/// Who made the modem and what it is, as far as the probes could tell
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModemIdentity {
+    pub manufacturer: String,
+    pub model: String,
+    pub revision: String,
+}
+
+impl ModemIdentity {
+    /// Later probes are more specific and win over earlier ones
+    fn update(&mut self, probe: &str, lines: &[&str]) {
+        match probe {
+            "ATI" => {
+                for (index, line) in lines.iter().enumerate() {
+                    match line.split_once(':') {
+                        Some(("Manufacturer", value)) => self.manufacturer = unquote(value),
+                        Some(("Model", value)) => self.model = unquote(value),
+                        Some(("Revision", value)) => self.revision = unquote(value),
+                        // Plain V.250 answer: manufacturer, model and revision on separate lines
+                        None if index == 0 => self.manufacturer = unquote(line),
+                        None if index == 1 => self.model = unquote(line),
+                        None if index == 2 => self.revision = unquote(line),
+                        _ => {}
+                    }
+                }
+            }
+            "AT+CGMI" => set_from(&mut self.manufacturer, lines, "+CGMI:", 0),
+            "AT+CGMM" => set_from(&mut self.model, lines, "+CGMM:", 0),
+            // +FMM: "<description>","<model>"
+            "AT+FMM?" => set_from(&mut self.model, lines, "+FMM:", 1),
+            "AT+GTPKGVER?" => set_from(&mut self.revision, lines, "+GTPKGVER:", 0),
+            _ => {}
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let known = |value: &str| if value.is_empty() { "unknown".to_string() } else { value.to_string() };
+        format!("{} {} (revision {})", known(&self.manufacturer), known(&self.model), known(&self.revision))
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+/// `field` of the first line, the `+CMD:` prefix is optional as some firmwares omit it.
+/// The last field is used when there are fewer.
+fn set_from(target: &mut String, lines: &[&str], prefix: &str, field: usize) {
+    let Some(line) = lines.first() else { return };
+    let fields: Vec<&str> = line.strip_prefix(prefix).unwrap_or(line).split(',').collect();
+    if let Some(value) = fields.get(field).or(fields.last()).map(|value| unquote(value)) {
+        if !value.is_empty() {
+            *target = value;
+        }
+    }
+}
+
+/// Bands that can be enabled on the connected modem
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandCapabilities {
+    pub umts: Vec<usize>,
+    pub lte: Vec<usize>,
+    pub nr: Vec<usize>,
+}
+
+impl BandCapabilities {
+    pub fn declared(profile: &dyn ModemProfile) -> BandCapabilities {
+        BandCapabilities {
+            umts: profile.supported_umts_bands().to_vec(),
+            lte: profile.supported_lte_bands().to_vec(),
+            nr: profile.supported_nr_bands().to_vec(),
+        }
+    }
+}
+
+static PARENTHESIZED: Lazy<Regex> = Lazy::new(|| Regex::new(r"\([^)]*\),?").unwrap());
+
+/// Band list of a Fibocom `=?` test response, e.g. `+XACT: (0-6),(0-2),0,1,2,4,5,8,101,103`.
+/// The parenthesized ranges are the other parameters; bands use the Fibocom encoding:
+/// <band> for UMTS, 100 + <band> for LTE and 5000 + <band> for NR.
+pub(crate) fn parse_fibocom_band_list(response: &str, prefix: &str) -> Option<BandCapabilities> {
+    let line = response.lines().find_map(|line| line.trim().strip_prefix(prefix))?;
+    let mut bands = BandCapabilities::default();
+    for band in PARENTHESIZED.replace_all(line, "").split(',').filter_map(|band| band.trim().parse::<usize>().ok()) {
+        match band {
+            0 => {}
+            band if band > 5000 => bands.nr.push(band - 5000),
+            band if band > 100 => bands.lte.push(band - 100),
+            band => bands.umts.push(band),
+        }
+    }
+    (bands != BandCapabilities::default()).then_some(bands)
+}
+
+/// Identifies the connected modem, picks its profile and finds the bands it supports.
+/// A modem no profile matches gets `GENERIC_3GPP` and `generic` set.
+pub fn detect_modem(session: &ModemSession) -> Result<Modem, AtError> {
+    let mut identity = ModemIdentity::default();
+    let mut transcript = String::new();
+    for probe in IDENTIFICATION_PROBES {
+        // Some modems never answer a vendor command they do not know
+        let response = match session.execute(probe) {
+            Ok(response) if response.is_ok() => response,
+            Ok(_) | Err(AtError::Timeout(_)) => continue,
+            Err(err) => return Err(err),
+        };
+        let lines: Vec<&str> = response.lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()).collect();
+        identity.update(probe, &lines);
+        transcript.push_str(&response.text());
+        transcript.push('\n');
+    }
+
+    let matched = PROFILES.iter().copied().find(|profile| profile.identification().is_match(&transcript));
+    let profile = matched.unwrap_or(&GENERIC_3GPP);
+    let bands = probe_bands(session, profile)?;
+    enable_registration_reports(session, profile)?;
+    Ok(Modem { profile, generic: matched.is_none(), identity, bands })
+}
+
+fn probe_bands(session: &ModemSession, profile: &dyn ModemProfile) -> Result<BandCapabilities, AtError> {
+    let Some(query) = profile.band_query() else {
+        return Ok(BandCapabilities::declared(profile));
+    };
+    let response = match session.execute(query) {
+        Ok(response) => response,
+        Err(AtError::Timeout(_)) => return Ok(BandCapabilities::declared(profile)),
+        Err(err) => return Err(err),
+    };
+    Ok(response.is_ok()
+        .then(|| profile.parse_supported_bands(&response.text()))
+        .flatten()
+        .unwrap_or_else(|| BandCapabilities::declared(profile)))
+}
+
+/// Location and reject cause in the registration answers and URCs of the domains the
+/// profile polls: `=3`, or `=2` without the reject cause where the modem does not know it.
+/// The modem keeps answering without them if both fail or time out.
+fn enable_registration_reports(session: &ModemSession, profile: &dyn ModemProfile) -> Result<(), AtError> {
+    let accepted = |command: String| match session.execute(&command) {
+        Ok(response) => Ok(response.is_ok()),
+        Err(AtError::Timeout(_)) => Ok(false),
+        Err(err) => Err(err),
+    };
+    for domain in RegistrationDomain::ALL {
+        let read = format!("{}?", domain.prefix());
+        if !profile.info_commands().iter().any(|command| command.contains(&read)) {
+            continue;
+        }
+        if !accepted(format!("AT{}=3", domain.prefix()))? {
+            accepted(format!("AT{}=2", domain.prefix()))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Modem {
+    pub(crate) profile: &'static dyn ModemProfile,
+    /// No vendor profile matched, only standard 3GPP commands are used
+    pub(crate) generic: bool,
+    pub(crate) identity: ModemIdentity,
+    pub(crate) bands: BandCapabilities,
+}
+
+impl Default for Modem {
+    fn default() -> Modem {
+        Modem { profile: &GENERIC_3GPP, generic: true, identity: ModemIdentity::default(), bands: BandCapabilities::default() }
+    }
+}
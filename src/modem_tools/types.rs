@@ -1,65 +1,150 @@
-use regex::Regex;
-use crate::modem_tools::converters::get_band_lte;
+use serde::Serialize;
+use crate::modem_tools::converters::{get_band_lte, lte_channel, nr_channel, split_nci, timing_advance_distance, umts_channel, DistanceEstimate, NrChannel, TimingAdvanceRat};
+use crate::modem_tools::parse_report::ParseReport;
+use crate::modem_tools::plmn::{Plmn, Roaming};
+use crate::modem_tools::registration::NetworkRegistration;
 use crate::display_tools::bars::get_bar;
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum BandModes {
     #[default]
-    LTE,
-    UMTS
+    Lte,
+    Umts,
+    Nr
 }
 
-pub struct AtRegexps {
-    pub cgmi_regex: Regex,
-    pub fmm_regex: Regex,
-    pub gtpkgver_regex: Regex,
-    pub cfsn_regex: Regex,
-    pub cgsn_regex: Regex,
-    pub cimi_regex: Regex,
-    pub csq_regex: Regex,
-    pub ccid_regex: Regex,
-    pub cgcontrdp_regex: Regex,
-    pub cops_regex: Regex,
-    pub xmci4_regex: Regex,
-    pub xmci45_regex: Regex,
-    pub xlec_regex: Regex,
-    pub xact_regex: Regex,
+/// Serving or neighbour cell as reported by `+XMCI`.
+#[derive(Default, Clone, Serialize)]
+pub struct CarrierInfo {
+    pub ci: i32,
+    /// eNB ID and local cell ID decoded from the ECI (`ci`)
+    pub enb_id: Option<u32>,
+    pub local_cell_id: Option<u32>,
+    pub pci: i32,
+    pub earfcn: i32,
+    #[serde(rename = "rsrp_dbm")]
+    pub rsrp: i32,
+    #[serde(rename = "rsrq_db")]
+    pub rsrq: i32,
+    #[serde(rename = "sinr_db")]
+    pub sinr: i32,
+    /// `None` when the modem does not report it, usually for other frequencies than the serving ones
+    #[serde(rename = "bandwidth_mhz")]
+    pub bandwidth: Option<f64>,
+    /// Only known for the serving cells
+    pub timing_advance: Option<u32>,
+    /// Set by `ModemInfo::estimate_distances` from the timing advance
+    pub distance: Option<DistanceEstimate>,
+}
+
+/// How the modem is attached to 5G
+#[derive(Default, Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NrMode {
+    /// LTE or UMTS only
+    #[default]
+    None,
+    /// Non-standalone, NR carriers added to an LTE anchor (EN-DC)
+    EnDc,
+    /// Standalone, NR core network
+    Sa,
 }
 
-#[derive(Default, Clone)]
+impl NrMode {
+    pub fn description(self) -> &'static str {
+        match self {
+            NrMode::None => "no 5G",
+            NrMode::EnDc => "5G NSA (EN-DC)",
+            NrMode::Sa => "5G SA",
+        }
+    }
+}
+
+/// NR serving or neighbour cell.
+#[derive(Default, Clone, Serialize)]
+pub struct NrCarrierInfo {
+    pub ci: i64,
+    /// gNB ID and local cell ID decoded from the NCI (`ci`), set by `ModemInfo::decode_nr_cell_ids`
+    /// as the gNB ID length is not known to the parser
+    pub gnb_id: Option<u32>,
+    pub local_cell_id: Option<u32>,
+    pub pci: i32,
+    pub nr_arfcn: i32,
+    pub band: Option<usize>,
+    #[serde(rename = "bandwidth_mhz")]
+    pub bandwidth: i32,
+    #[serde(rename = "ss_rsrp_dbm")]
+    pub ss_rsrp: i32,
+    #[serde(rename = "ss_rsrq_db")]
+    pub ss_rsrq: i32,
+    #[serde(rename = "ss_sinr_db")]
+    pub ss_sinr: i32,
+}
+
+/// Field names and units of the serialized form are part of the JSON/TOML
+/// export and should not change.
+#[derive(Default, Clone, Serialize)]
 pub struct ModemInfo {
-    pub manufacturer: String,
-    pub model: String,
-    pub fw_version: String,
-    pub serial_number: String,
-    pub imei: String,
-    pub imsi: String,
-    pub iccid: String,
-    pub operator: String,
-    pub mode: String,
-    pub ip: String,
-    pub mask: String,
-    pub gw: String,
-    pub dns_prim: String,
-    pub dns_sec: String,
-    pub band: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub fw_version: Option<String>,
+    pub serial_number: Option<String>,
+    pub imei: Option<String>,
+    pub imsi: Option<String>,
+    pub iccid: Option<String>,
+    pub operator: Option<String>,
+    /// Serving network
+    pub network: Option<Plmn>,
+    /// Network that issued the SIM, from the IMSI
+    pub home_network: Option<Plmn>,
+    pub roaming: Roaming,
+    /// CS, PS, EPS and 5GS registration, also updated by the URCs between polls
+    pub registration: NetworkRegistration,
+    pub mode: Option<String>,
+    /// Access point name of the first PDP context
+    pub apn: Option<String>,
+    pub ip: Option<String>,
+    pub mask: Option<String>,
+    pub gw: Option<String>,
+    pub dns_prim: Option<String>,
+    pub dns_sec: Option<String>,
+    pub band: Option<String>,
     pub config_band_modes: Vec<BandModes>,
     pub enabled_lte_bands: Vec<usize>,
     pub enabled_umts_bands: Vec<usize>,
-    pub distance: f64,
-    pub dluarfnc: i32,
-    pub csq: i32,
-    pub csq_perc: i32,
-    pub rssi: i32,
-    pub sinr: i32,
-    pub rsrp: i32,
-    pub rsrq: i32,
-    pub ci_x: Vec<i32>,
-    pub pci_x: Vec<i32>,
-    pub earfcn_x: Vec<i32>,
-    pub rsrp_x: Vec<i32>,
-    pub rsrq_x: Vec<i32>,
-    pub sinr_x: Vec<i32>,
+    pub enabled_nr_bands: Vec<usize>,
+    #[serde(rename = "distance_m")]
+    pub distance: Option<f64>,
+    #[serde(rename = "earfcn")]
+    pub dluarfnc: Option<i32>,
+    pub csq: Option<i32>,
+    #[serde(rename = "csq_percent")]
+    pub csq_perc: Option<i32>,
+    #[serde(rename = "rssi_dbm")]
+    pub rssi: Option<i32>,
+    #[serde(rename = "sinr_db")]
+    pub sinr: Option<i32>,
+    #[serde(rename = "rsrp_dbm")]
+    pub rsrp: Option<i32>,
+    #[serde(rename = "rsrq_db")]
+    pub rsrq: Option<i32>,
+    /// GSM received level and bit error rate (RXQUAL 0-7), from `+CESQ`
+    #[serde(rename = "rxlev_dbm")]
+    pub rxlev: Option<i32>,
+    pub ber: Option<i32>,
+    /// Of the GSM serving cell, from `+XMCI`
+    pub gsm_timing_advance: Option<u32>,
+    /// UMTS received signal code power and Ec/Io (Ec/No in `+CESQ`), from `+CESQ`
+    #[serde(rename = "rscp_dbm")]
+    pub rscp: Option<i32>,
+    #[serde(rename = "ecio_db")]
+    pub ecio: Option<i32>,
+    pub carriers: Vec<CarrierInfo>,
+    pub nr_mode: NrMode,
+    pub nr_carriers: Vec<NrCarrierInfo>,
+    /// Responses of the poll that could not be parsed
+    pub parse_report: ParseReport,
 }
 
 impl ModemInfo {
@@ -69,56 +154,199 @@ impl ModemInfo {
         Firmware Version:    {}\n\
         Serial Number:       {}\n\
         IMEI:                {}\n",
-        &self.manufacturer,
-        &self.model,
-        &self.fw_version,
-        &self.serial_number,
-        &self.imei)
+        text(&self.manufacturer),
+        text(&self.model),
+        text(&self.fw_version),
+        text(&self.serial_number),
+        text(&self.imei))
     }
 
     pub fn display_signal_info(&self) -> String {
-        format!("Operator:             {} ({})\n\
+        format!("Registration:         {}\n\
+            Operator:             {} ({})\n\
+            Network:              {}\n\
+            Roaming:              {}\n\
             IP/Mask:              {} / {}\n\
             DNS:                  {} {}\n\
-            Distance:             {}m\n\n\
+            Distance:             {}\n\n\
             Signal:               {:>2}%     [{}]\n\
             RSSI:                 {:>2}dBm  [{}]\n\
-            SINR:                 {:>2}dB    [{}]\n\
-            RSRP:                 {:>2}dBm  [{}]\n\
-            RSRQ:                 {:>2}db   [{}]\n\
+            {}\
             Band:                 {}\n\
-                EARFCN:               {}\n\
+                {}:               {}{}\n\
             ",
-            self.operator, self.mode,
-            self.ip, self.mask,
-            self.dns_prim, self.dns_sec,
-            self.distance,
-            self.csq_perc, get_bar(self.csq_perc, 0, 100),
-            self.rssi, get_bar(self.rssi, -110, -25),
-            self.sinr, get_bar(self.sinr, -10, 30),
-            self.rsrp, get_bar(self.rsrp, -120, -50),
-            self.rsrq, get_bar(self.rsrq, -25, -1),
-            self.band,
-            self.dluarfnc
+            self.registration.current().map_or("--".to_string(), |(domain, registration)| registration.describe(domain)),
+            text(&self.operator), text(&self.mode),
+            self.network.as_ref().map_or("--".to_string(), |network| network.to_string()),
+            self.display_roaming(),
+            text(&self.ip), text(&self.mask),
+            text(&self.dns_prim), text(&self.dns_sec),
+            self.serving_distance().map_or("--".to_string(), |estimate| estimate.to_string()),
+            number(self.csq_perc), option_bar(self.csq_perc, 0, 100),
+            number(self.rssi), option_bar(self.rssi, -110, -25),
+            self.display_rat_metrics(),
+            text(&self.band),
+            if self.is_umts() { "UARFCN" } else { "EARFCN" },
+            number(self.dluarfnc),
+            self.serving_channel().map_or(String::new(), |channel| format!(" ({})", channel))
             )
     }
+
+    /// Measurements of the access technology in use, LTE ones unless on GSM or UMTS
+    fn display_rat_metrics(&self) -> String {
+        if self.is_umts() {
+            format!("RSCP:                 {:>2}dBm  [{}]\n\
+                Ec/Io:                {:>2}dB    [{}]\n",
+                number(self.rscp), option_bar(self.rscp, -120, -25),
+                number(self.ecio), option_bar(self.ecio, -24, 0))
+        } else if self.is_gsm() {
+            format!("RXLEV:                {:>2}dBm  [{}]\n\
+                BER:                  {:>2}\n",
+                number(self.rxlev), option_bar(self.rxlev, -110, -48),
+                number(self.ber))
+        } else {
+            format!("SINR:                 {:>2}dB    [{}]\n\
+                RSRP:                 {:>2}dBm  [{}]\n\
+                RSRQ:                 {:>2}db   [{}]\n",
+                number(self.sinr), option_bar(self.sinr, -10, 30),
+                number(self.rsrp), option_bar(self.rsrp, -120, -50),
+                number(self.rsrq), option_bar(self.rsrq, -25, -1))
+        }
+    }
+
+    /// Serving on 3G, from the access technology of `+COPS`
+    pub fn is_umts(&self) -> bool {
+        matches!(self.mode.as_deref(), Some("UMTS" | "HSDPA" | "HSUPA" | "HSPA"))
+    }
+
+    fn is_gsm(&self) -> bool {
+        matches!(self.mode.as_deref(), Some("GSM" | "EDGE"))
+    }
+
+    fn display_roaming(&self) -> String {
+        match (self.roaming, &self.home_network) {
+            (Roaming::National | Roaming::International, Some(home)) => format!("{} (home network {})", self.roaming.description(), home),
+            _ => self.roaming.description().to_string(),
+        }
+    }
+
+    /// Splits the NR cell identities with the gNB ID length used by the operator
+    pub fn decode_nr_cell_ids(&mut self, gnb_id_bits: u32) {
+        for carrier in self.nr_carriers.iter_mut() {
+            let cell = split_nci(carrier.ci, gnb_id_bits);
+            carrier.gnb_id = cell.map(|(gnb_id, _)| gnb_id);
+            carrier.local_cell_id = cell.map(|(_, cell_id)| cell_id);
+        }
+    }
+
+    /// Distance to each cell with a timing advance, and to the serving cell of the access
+    /// technology in use
+    pub fn estimate_distances(&mut self) {
+        for carrier in self.carriers.iter_mut() {
+            carrier.distance = carrier.timing_advance
+                .and_then(|timing_advance| timing_advance_distance(TimingAdvanceRat::Lte, timing_advance));
+        }
+        self.distance = self.serving_distance().map(|estimate| estimate.distance);
+    }
+
+    fn serving_distance(&self) -> Option<DistanceEstimate> {
+        if self.is_gsm() {
+            return self.gsm_timing_advance.and_then(|timing_advance| timing_advance_distance(TimingAdvanceRat::Gsm, timing_advance));
+        }
+        match self.nr_mode {
+            // No modem reports the NR timing advance
+            NrMode::Sa => None,
+            _ => self.carriers.first().and_then(|carrier| carrier.distance),
+        }
+    }
+
+    /// Compares the serving network with the home network, once both are known
+    pub fn update_roaming(&mut self) {
+        self.roaming = Roaming::between(self.home_network.as_ref(), self.network.as_ref());
+    }
+
+    /// Band and frequency of the serving channel, read according to the access technology in use
+    fn serving_channel(&self) -> Option<String> {
+        match (self.mode.as_deref(), self.dluarfnc) {
+            (_, Some(uarfcn)) if self.is_umts() => umts_channel(uarfcn).map(|channel| channel.to_string()),
+            (Some("NR"), _) => self.nr_carriers.first().and_then(|carrier| nr_channel(carrier.nr_arfcn, carrier.band)).map(|channel| channel.to_string()),
+            (_, Some(earfcn)) if earfcn > 0 => lte_channel(earfcn).map(|channel| channel.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn display_carrier_info(&self) -> String {
         let mut carrier_info: String = "".to_string();
-        for (index, &_) in self.ci_x.iter().enumerate(){
-            let band = get_band_lte(self.earfcn_x[index]);
-            let rsrp_str = format!("{}dBm", self.rsrp_x[index]);
-            let rsrq_str = format!("{}dB", self.rsrq_x[index]);
-            let sinr_str = format!("{}dB", self.sinr_x[index]);
+        for (index, carrier) in self.carriers.iter().enumerate() {
+            let band = get_band_lte(carrier.earfcn);
+            let rsrp_str = format!("{}dBm", carrier.rsrp);
+            let rsrq_str = format!("{}dB", carrier.rsrq);
+            let sinr_str = format!("{}dB", carrier.sinr);
 
-            let rsrp_bar = get_bar(self.rsrp_x[index], -12, -50);
-            let rsrq_bar = get_bar(self.rsrq_x[index], -25, -1);
-            let sinr_bar = get_bar(self.sinr_x[index], -10, 30);
+            let rsrp_bar = get_bar(carrier.rsrp, -12, -50);
+            let rsrq_bar = get_bar(carrier.rsrq, -25, -1);
+            let sinr_bar = get_bar(carrier.sinr, -10, 30);
+
+            let frequencies = lte_channel(carrier.earfcn).map_or("--".to_string(), |channel| channel.frequencies());
+            let enb = cell_label(carrier.enb_id, carrier.local_cell_id);
+            let distance = distance_label(carrier.distance);
+            let bandwidth = carrier.bandwidth.map_or("--".to_string(), |bandwidth| format!("{}MHz", bandwidth));
 
             carrier_info.push_str(format!(
-                "===Carrier {:2}: CI: {:8} PCI: {:4} Band (EARFCN): {:3} ({:5}) RSRP: {:>5} [{}] RSRQ: {:>5} [{}] SINR: {:2} [{}]\n",
-                index, self.ci_x[index], self.pci_x[index], band, self.earfcn_x[index], rsrp_str, rsrp_bar, rsrq_str, rsrq_bar, sinr_str, sinr_bar
+                "===Carrier {:2}: CI: {:8} eNB: {:>9} PCI: {:4} Band (EARFCN): {:3} ({:5}) {} BW: {:>6} RSRP: {:>5} [{}] RSRQ: {:>5} [{}] SINR: {:2} [{}]{}\n",
+                index, carrier.ci, enb, carrier.pci, band, carrier.earfcn, frequencies, bandwidth,
+                rsrp_str, rsrp_bar, rsrq_str, rsrq_bar, sinr_str, sinr_bar, distance
+            ).as_str());
+        }
+        if self.nr_mode != NrMode::None {
+            carrier_info.push_str(format!("===NR: {}\n", self.nr_mode.description()).as_str());
+        }
+        for (index, carrier) in self.nr_carriers.iter().enumerate() {
+            let channel = nr_channel(carrier.nr_arfcn, carrier.band);
+            let band = carrier.band.map(|band| format!("n{}", band))
+                .or_else(|| channel.as_ref().and_then(NrChannel::band_label))
+                .unwrap_or("Unknown".to_string());
+            let rsrp_str = format!("{}dBm", carrier.ss_rsrp);
+            let rsrq_str = format!("{}dB", carrier.ss_rsrq);
+            let sinr_str = format!("{}dB", carrier.ss_sinr);
+            let frequencies = channel.map_or("--".to_string(), |channel| channel.frequencies());
+            let gnb = cell_label(carrier.gnb_id, carrier.local_cell_id);
+
+            carrier_info.push_str(format!(
+                "===NR Carrier {:2}: CI: {:8} gNB: {:>12} PCI: {:4} Band (NR-ARFCN): {:4} ({:6}) {} BW: {:3}MHz SS-RSRP: {:>6} [{}] SS-RSRQ: {:>5} [{}] SS-SINR: {:>4} [{}]\n",
+                index, carrier.ci, gnb, carrier.pci, band, carrier.nr_arfcn, frequencies, carrier.bandwidth,
+                rsrp_str, get_bar(carrier.ss_rsrp, -120, -50),
+                rsrq_str, get_bar(carrier.ss_rsrq, -25, -1),
+                sinr_str, get_bar(carrier.ss_sinr, -10, 30)
             ).as_str());
         }
         carrier_info
     }
 }
+
+/// `--` for a value the modem did not give
+fn text(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("--")
+}
+
+fn number(value: Option<i32>) -> String {
+    value.map_or("--".to_string(), |value| value.to_string())
+}
+
+/// An empty bar for an unknown value
+fn option_bar(value: Option<i32>, min: i32, max: i32) -> String {
+    value.map_or("░░░░░".to_string(), |value| get_bar(value, min, max))
+}
+
+/// `40067-3`, the base station and sector notation of cell databases
+fn cell_label(node_id: Option<u32>, cell_id: Option<u32>) -> String {
+    match (node_id, cell_id) {
+        (Some(node_id), Some(cell_id)) => format!("{}-{}", node_id, cell_id),
+        _ => "--".to_string(),
+    }
+}
+
+/// Appended to the carriers that have a timing advance, the serving ones
+fn distance_label(distance: Option<DistanceEstimate>) -> String {
+    distance.map_or(String::new(), |distance| format!(" Distance: {}", distance))
+}
@@ -0,0 +1,92 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UrcKind {
+    Ring,
+    NewMessage,
+    CsRegistration,
+    PsRegistration,
+    EpsRegistration,
+    NrRegistration,
+    CarrierAggregation,
+    Other,
+}
+
+/// Unsolicited result code: a line the modem sent on its own rather than in
+/// answer to the command in flight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Urc {
+    pub kind: UrcKind,
+    pub line: String,
+}
+
+static KNOWN_URCS: &[(&str, UrcKind)] = &[
+    ("RING", UrcKind::Ring),
+    ("+CRING", UrcKind::Ring),
+    ("+CMTI", UrcKind::NewMessage),
+    ("+CMT", UrcKind::NewMessage),
+    ("+CDSI", UrcKind::NewMessage),
+    ("+CREG", UrcKind::CsRegistration),
+    ("+CGREG", UrcKind::PsRegistration),
+    ("+CEREG", UrcKind::EpsRegistration),
+    ("+C5GREG", UrcKind::NrRegistration),
+    ("+XLEC", UrcKind::CarrierAggregation),
+];
+
+impl Urc {
+    /// Recognise `line` as a known URC. Lines whose prefix is named in
+    /// `command` are its solicited answer (`AT+CREG?` replies with `+CREG:`)
+    /// and are left to the response.
+    pub fn parse(line: &str, command: Option<&str>) -> Option<Urc> {
+        let prefix = line.split(':').next().unwrap_or(line).trim();
+        let kind = KNOWN_URCS.iter()
+            .find(|(name, _)| *name == prefix)
+            .map(|(_, kind)| *kind)?;
+
+        if let Some(command) = command {
+            if prefix.starts_with('+') && answers_with(command, prefix) {
+                return None;
+            }
+        }
+        Some(Urc { kind, line: line.to_string() })
+    }
+
+    /// Any line received while no command is in flight.
+    pub fn unsolicited(line: &str) -> Urc {
+        Urc::parse(line, None).unwrap_or(Urc { kind: UrcKind::Other, line: line.to_string() })
+    }
+
+    /// Whether this event makes the last polled modem state stale.
+    pub fn changes_modem_state(&self) -> bool {
+        !matches!(self.kind, UrcKind::Ring | UrcKind::Other)
+    }
+}
+
+/// Whether one of the commands in `command`, `AT+XCCINFO?; +XLEC?` is two, is named
+/// `prefix` itself: `+CMT` is not the answer to `AT+CMTI?`
+fn answers_with(command: &str, prefix: &str) -> bool {
+    let command = command.to_ascii_uppercase();
+    let command = command.strip_prefix("AT").unwrap_or(&command);
+    command.split(';').any(|part| {
+        part.trim().strip_prefix(prefix).is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric()))
+    })
+}
+
+/// Fans URCs out to every subscriber; subscribers that went away are dropped.
+#[derive(Clone, Default)]
+pub struct UrcDispatcher {
+    subscribers: Arc<Mutex<Vec<Sender<Urc>>>>,
+}
+
+impl UrcDispatcher {
+    pub fn subscribe(&self) -> Receiver<Urc> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub fn publish(&self, urc: Urc) {
+        self.subscribers.lock().unwrap().retain(|subscriber| subscriber.send(urc.clone()).is_ok());
+    }
+}
@@ -1 +1,2 @@
-pub mod serial_reader;
\ No newline at end of file
+pub mod serial_reader;
+pub mod transport;
\ No newline at end of file
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use serialport::SerialPort;
+use crate::modem_tools::modem::COMMAND_TIMEOUT;
+use crate::modem_tools::recording::ReplayTransport;
+use crate::modem_tools::simulator::{Scenario, SimulatedModem};
+
+/// Byte stream leading to the modem AT interpreter.
+///
+/// Reads must give up after the read timeout the transport was opened with and
+/// report it as `io::ErrorKind::TimedOut`, so the session can look for
+/// unsolicited result codes between requests.
+pub trait Transport: Read + Write + Send {}
+
+/// Where the modem AT port can be reached:
+/// `/dev/ttyACM0` or `COM3`, `tcp://router:2000` (ser2net, socat), `unix:///run/modem.sock`,
+/// `sim://` / `sim://scenario.txt` for the built-in simulator, or `replay://session.txt`
+/// to play back a recorded session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+    Serial(String),
+    Tcp(String),
+    Unix(String),
+    Simulator(Option<String>),
+    Replay(String),
+}
+
+impl Endpoint {
+    pub fn parse(endpoint: &str) -> Endpoint {
+        let endpoint = endpoint.trim();
+        if let Some(address) = endpoint.strip_prefix("tcp://") {
+            Endpoint::Tcp(address.to_string())
+        } else if let Some(path) = endpoint.strip_prefix("unix://") {
+            Endpoint::Unix(path.to_string())
+        } else if let Some(scenario) = endpoint.strip_prefix("sim://") {
+            Endpoint::Simulator(Some(scenario.to_string()).filter(|s| !s.is_empty()))
+        } else if let Some(path) = endpoint.strip_prefix("replay://") {
+            Endpoint::Replay(path.to_string())
+        } else {
+            Endpoint::Serial(endpoint.to_string())
+        }
+    }
+
+    pub fn open(&self, baud_rate: u32, read_timeout: Duration) -> Result<Box<dyn Transport>, io::Error> {
+        match self {
+            Endpoint::Serial(port_name) => Ok(Box::new(SerialTransport::open(port_name, baud_rate, read_timeout)?)),
+            Endpoint::Tcp(address) => Ok(Box::new(TcpTransport::connect(address, read_timeout)?)),
+            Endpoint::Unix(path) => open_unix(path, read_timeout),
+            Endpoint::Simulator(scenario) => {
+                let scenario = match scenario {
+                    Some(path) => Scenario::load(path)?,
+                    None => Scenario::default(),
+                };
+                Ok(Box::new(SimulatedModem::new(scenario, read_timeout)))
+            }
+            Endpoint::Replay(path) => Ok(Box::new(ReplayTransport::open(path, read_timeout)?)),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Serial(port_name) => write!(f, "{}", port_name),
+            Endpoint::Tcp(address) => write!(f, "tcp://{}", address),
+            Endpoint::Unix(path) => write!(f, "unix://{}", path),
+            Endpoint::Simulator(scenario) => write!(f, "sim://{}", scenario.as_deref().unwrap_or("")),
+            Endpoint::Replay(path) => write!(f, "replay://{}", path),
+        }
+    }
+}
+
+/// Local tty or COM port.
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialTransport {
+    pub fn open(port_name: &str, baud_rate: u32, read_timeout: Duration) -> Result<SerialTransport, io::Error> {
+        let mut port = serialport::new(port_name, baud_rate)
+            .timeout(read_timeout)
+            .open()?;
+        port.write_data_terminal_ready(true)?; // Включение DTR
+        Ok(SerialTransport { port })
+    }
+}
+
+impl Read for SerialTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.port.read(buf)
+    }
+}
+
+impl Write for SerialTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Transport for SerialTransport {}
+
+/// Raw TCP connection to a serial port server such as ser2net or socat.
+///
+/// Connecting and writing give up after the command timeout, so an unreachable
+/// host fails like a modem that does not answer instead of hanging for minutes.
+pub struct TcpTransport {
+    stream: TcpStream,
+}
+
+impl TcpTransport {
+    pub fn connect(address: &str, read_timeout: Duration) -> Result<TcpTransport, io::Error> {
+        let stream = connect_any(address)?;
+        stream.set_read_timeout(Some(read_timeout))?;
+        stream.set_write_timeout(Some(COMMAND_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        Ok(TcpTransport { stream })
+    }
+}
+
+/// First address `address` resolves to that accepts the connection in time
+fn connect_any(address: &str) -> Result<TcpStream, io::Error> {
+    let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, format!("{} resolves to no address", address));
+    for socket_address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_address, COMMAND_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            Ok(0) => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "connection closed by peer")),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::ErrorKind::TimedOut.into()),
+            result => result,
+        }
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Transport for TcpTransport {}
+
+/// Unix domain socket, e.g. one exported by `socat UNIX-LISTEN:...`.
+#[cfg(unix)]
+pub struct UnixTransport {
+    stream: std::os::unix::net::UnixStream,
+}
+
+#[cfg(unix)]
+impl UnixTransport {
+    pub fn connect(path: &str, read_timeout: Duration) -> Result<UnixTransport, io::Error> {
+        let stream = std::os::unix::net::UnixStream::connect(path)?;
+        stream.set_read_timeout(Some(read_timeout))?;
+        stream.set_write_timeout(Some(COMMAND_TIMEOUT))?;
+        Ok(UnixTransport { stream })
+    }
+}
+
+#[cfg(unix)]
+impl Read for UnixTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            Ok(0) => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "connection closed by peer")),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Err(io::ErrorKind::TimedOut.into()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Write for UnixTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixTransport {}
+
+#[cfg(unix)]
+fn open_unix(path: &str, read_timeout: Duration) -> Result<Box<dyn Transport>, io::Error> {
+    Ok(Box::new(UnixTransport::connect(path, read_timeout)?))
+}
+
+#[cfg(not(unix))]
+fn open_unix(_path: &str, _read_timeout: Duration) -> Result<Box<dyn Transport>, io::Error> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "unix sockets are not available on this platform"))
+}
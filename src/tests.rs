@@ -1,6 +1,12 @@
 #[cfg(test)]
-mod tests {
-    use crate::modem_tools::modem::REGEXPS;
+mod regex_tests {
+    use crate::modem_tools::parsers::cgcontrdp::CGCONTRDP_REGEX;
+    use crate::modem_tools::parsers::cops::COPS_REGEX;
+    use crate::modem_tools::parsers::csq::CSQ_REGEX;
+    use crate::modem_tools::parsers::identity::{CCID_REGEX, CFSN_REGEX, CGMI_REGEX, CGSN_REGEX, CIMI_REGEX, FMM_REGEX, GTPKGVER_REGEX};
+    use crate::modem_tools::parsers::xact::XACT_REGEX;
+    use crate::modem_tools::parsers::xlec::XLEC_REGEX;
+    use crate::modem_tools::parsers::xmci::XMCI_REGEX;
 
     // static TEST_STRING: &str = "AT+CSQ?\r\r\n+CSQ: 11,2\r\n\r\nOK\r\n\
     // AT+XCCINFO?; +XLEC?; +XMCI=1\r\r\n\
@@ -37,14 +43,14 @@ mod tests {
 
     #[test]
     fn test_cgmi_regex() {
-        let re_cgmi = &REGEXPS.cgmi_regex;
+        let re_cgmi = &*CGMI_REGEX;
         let caps = re_cgmi.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "Fibocom");
     }
 
     #[test]
     fn test_fmm_regex() {
-        let re_cgmi = &REGEXPS.fmm_regex;
+        let re_cgmi = &*FMM_REGEX;
         let caps = re_cgmi.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "L850 LTE Module");
         assert_eq!(caps.get(2).unwrap().as_str(), "L850");
@@ -52,50 +58,54 @@ mod tests {
 
     #[test]
     fn test_gtpkgver_regex() {
-        let re_gtpkgver = &REGEXPS.gtpkgver_regex;
+        let re_gtpkgver = &*GTPKGVER_REGEX;
         let caps = re_gtpkgver.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "18500.5001.00.05.27.30_5001.05.001.035");
     }
 
     #[test]
     fn test_cfsn_regex() {
-        let re_cfsn = &REGEXPS.cfsn_regex;
+        let re_cfsn = &*CFSN_REGEX;
         let caps = re_cfsn.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "D1M2LG1EA3");
     }
 
     #[test]
     fn test_cgsn_regex() {
-        let re_cgsn = &REGEXPS.cgsn_regex;
+        let re_cgsn = &*CGSN_REGEX;
         let caps = re_cgsn.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "015550006919978");
     }
 
     #[test]
     fn test_cimi_regex() {
-        let re_cimi = &REGEXPS.cimi_regex;
+        let re_cimi = &*CIMI_REGEX;
         let caps = re_cimi.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "220033400995562");
     }
 
     #[test]
     fn test_ccid_regex() {
-        let re_ccid = &REGEXPS.ccid_regex;
+        let re_ccid = &*CCID_REGEX;
         let caps = re_ccid.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "89381030000328789401");
     }
 
     #[test]
     fn test_cops_regex() {
-        let re_cops = &REGEXPS.cops_regex;
+        let re_cops = &*COPS_REGEX;
         let caps = re_cops.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(3).unwrap().as_str(), "mt:s");
         assert_eq!(caps.get(4).unwrap().as_str(), "7");
+        // No operator selected, or no <AcT> from a single-technology modem
+        assert!(re_cops.captures("+COPS: 0").unwrap().get(3).is_none());
+        assert!(re_cops.captures("+COPS: 0,0,\"mt:s\"").unwrap().get(4).is_none());
+        assert!(re_cops.captures("+COPS: 0,0").is_none());
     }
 
     #[test]
 fn test_cgcontrdp_regex() {
-    let re_cgcontrdp = &REGEXPS.cgcontrdp_regex;
+    let re_cgcontrdp = &*CGCONTRDP_REGEX;
     let caps = re_cgcontrdp.captures(TEST_STRING).unwrap();
     assert_eq!(caps.name("index").unwrap().as_str(), "1");
     assert_eq!(caps.name("cid").unwrap().as_str(), "6");
@@ -112,7 +122,7 @@ fn test_cgcontrdp_regex() {
 
     #[test]
     fn test_csq_regex() {
-        let re_csq = &REGEXPS.csq_regex;
+        let re_csq = &*CSQ_REGEX;
         let caps = re_csq.captures(TEST_STRING).unwrap();
         assert_eq!(caps.get(1).unwrap().as_str(), "11");
         assert_eq!(caps.get(2).unwrap().as_str(), "2");
@@ -120,7 +130,7 @@ fn test_cgcontrdp_regex() {
 
     #[test]
     fn test_xlec_regex() {
-        let re_xlec = &REGEXPS.xlec_regex;
+        let re_xlec = &*XLEC_REGEX;
         let caps = re_xlec.captures(TEST_STRING).unwrap();
         assert_eq!(caps.name("no_of_cells").unwrap().as_str(), "2");
         assert_eq!(caps.name("bw").unwrap().as_str(), "5,3");
@@ -129,7 +139,7 @@ fn test_cgcontrdp_regex() {
 
     #[test]
     fn test_xmci_regex() {
-        let re_xmci = &REGEXPS.xmci4_regex;
+        let re_xmci = &*XMCI_REGEX;
         // let test_string = r#"+XMCI: 4,310,260,"7012","A1B2","C3D4","E5F6","1234","0",97,20,40,"00A1","10""#;
         let caps = re_xmci.captures(TEST_STRING).unwrap();
         assert_eq!(caps.name("type").unwrap().as_str(), "4");
@@ -150,7 +160,7 @@ fn test_cgcontrdp_regex() {
 
     #[test]
     fn test_xact(){
-        let re_bands = &REGEXPS.xact_regex;
+        let re_bands = &*XACT_REGEX;
         let caps = re_bands.captures(TEST_STRING).unwrap();
         let umts_flag = caps.name("umts_flag").map_or("", |m| m.as_str());
         let lte_flag = caps.name("lte_flag").map_or("", |m| m.as_str());
@@ -179,7 +189,7 @@ fn test_cgcontrdp_regex() {
     #[test]
     fn test_lte_only() {
         let xact_string = "+XACT: 2,1,,101,103,120";
-        let re_bands = &REGEXPS.xact_regex;
+        let re_bands = &*XACT_REGEX;
 
         if let Some(caps) = re_bands.captures(xact_string) {
             let umts_flag = caps.name("umts_flag").map_or("", |m| m.as_str());
@@ -213,7 +223,7 @@ fn test_cgcontrdp_regex() {
     #[test]
     fn test_both_modes_limited_bands() {
         let xact_string = "+XACT: 4,2,1,1,2,4,5,8,101,103,120";
-        let re_bands = &REGEXPS.xact_regex;
+        let re_bands = &*XACT_REGEX;
 
         if let Some(caps) = re_bands.captures(xact_string) {
             let umts_flag = caps.name("umts_flag").map_or("", |m| m.as_str());
@@ -244,3 +254,1364 @@ fn test_cgcontrdp_regex() {
         }
     }
 }
+
+#[cfg(test)]
+mod at_response_tests {
+    use crate::modem_tools::at_response::{FinalResult, ResponseReader};
+    use crate::modem_tools::urc::{Urc, UrcKind};
+
+    #[test]
+    fn test_reader_waits_for_final_line() {
+        let mut reader = ResponseReader::default();
+        reader.expect("AT+COPS?");
+        // "OK" inside an operator name must not end the response
+        assert!(reader.feed(b"AT+COPS?\r\r\n+COPS: 0,0,\"BOOK").is_none());
+        assert!(reader.feed(b"ERS\",7\r\n").is_none());
+        let response = reader.feed(b"\r\nOK\r\n").unwrap();
+        assert_eq!(response.lines, vec!["+COPS: 0,0,\"BOOKERS\",7"]);
+        assert_eq!(response.result, FinalResult::Ok);
+        assert!(response.is_ok());
+    }
+
+    #[test]
+    fn test_reader_cme_error() {
+        let mut reader = ResponseReader::default();
+        reader.expect("AT+CIMI");
+        let response = reader.feed(b"AT+CIMI\r\r\n+CME ERROR: 10\r\n").unwrap();
+        assert!(response.lines.is_empty());
+        assert_eq!(response.result, FinalResult::CmeError(10));
+        assert_eq!(response.to_string(), "+CME ERROR: 10 (SIM not inserted)");
+    }
+
+    #[test]
+    fn test_final_result_codes() {
+        assert_eq!(FinalResult::parse("ERROR"), Some(FinalResult::Error));
+        assert_eq!(FinalResult::parse("NO CARRIER"), Some(FinalResult::NoCarrier));
+        assert_eq!(FinalResult::parse("+CMS ERROR: 321"), Some(FinalResult::CmsError(321)));
+        assert_eq!(FinalResult::parse("+CME ERROR: SIM PIN required"), Some(FinalResult::CmeError(11)));
+        assert_eq!(FinalResult::parse("CONNECT 150000000"), Some(FinalResult::Connect(Some("150000000".to_string()))));
+        assert_eq!(FinalResult::parse("+CSQ: 11,2"), None);
+        assert_eq!(FinalResult::parse("OK, maybe"), None);
+        assert_eq!(FinalResult::CmsError(321).description(), Some("invalid memory index"));
+    }
+
+    #[test]
+    fn test_reader_separates_urcs() {
+        let mut reader = ResponseReader::default();
+        reader.expect("AT+CSQ?");
+        let response = reader.feed(b"AT+CSQ?\r\r\n+CEREG: 1,\"4E91\",\"00009C03\",7\r\n+CSQ: 11,2\r\nRING\r\n\r\nOK\r\n").unwrap();
+        assert_eq!(response.lines, vec!["+CSQ: 11,2"]);
+
+        let urcs = reader.take_unsolicited();
+        assert_eq!(urcs.len(), 2);
+        assert_eq!(urcs[0].kind, UrcKind::EpsRegistration);
+        assert_eq!(urcs[1].kind, UrcKind::Ring);
+        assert!(reader.take_unsolicited().is_empty());
+    }
+
+    #[test]
+    fn test_reader_keeps_solicited_urc_prefix() {
+        let mut reader = ResponseReader::default();
+        reader.expect("AT+XCCINFO?; +XLEC?; +XMCI=1");
+        let response = reader.feed(b"+XLEC: 0,2,5,3,BAND_LTE_3\r\nOK\r\n").unwrap();
+        assert_eq!(response.lines, vec!["+XLEC: 0,2,5,3,BAND_LTE_3"]);
+        assert!(reader.take_unsolicited().is_empty());
+    }
+
+    #[test]
+    fn test_urc_prefix_of_command() {
+        assert_eq!(Urc::parse("+CREG: 1", Some("at+creg?")), None);
+        assert_eq!(Urc::parse("+XLEC: 0,1,5,BAND_LTE_3", Some("AT+XCCINFO?; +XLEC?")), None);
+        // Only the command name counts, not a longer one or its arguments
+        assert_eq!(Urc::parse("+CMT: \"+4912345\",,\"24/10/18\"", Some("AT+CMTI?")).map(|urc| urc.kind), Some(UrcKind::NewMessage));
+        assert_eq!(Urc::parse("+CREG: 1", Some("AT+CPBF=\"+CREG\"")).map(|urc| urc.kind), Some(UrcKind::CsRegistration));
+    }
+
+    #[test]
+    fn test_reader_idle_lines_are_unsolicited() {
+        let mut reader = ResponseReader::default();
+        assert!(reader.feed(b"\r\n+CMTI: \"SM\",3\r\n").is_none());
+        let urcs = reader.take_unsolicited();
+        assert_eq!(urcs.len(), 1);
+        assert_eq!(urcs[0].kind, UrcKind::NewMessage);
+        assert_eq!(urcs[0].line, "+CMTI: \"SM\",3");
+        assert!(urcs[0].changes_modem_state());
+    }
+}
+
+#[cfg(test)]
+mod transport_tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use crate::modem_tools::at_response::FinalResult;
+    use crate::modem_tools::session::ModemSession;
+    use crate::serial_tools::transport::Endpoint;
+
+    #[test]
+    fn test_endpoint_parse() {
+        assert_eq!(Endpoint::parse("/dev/ttyACM0"), Endpoint::Serial("/dev/ttyACM0".to_string()));
+        assert_eq!(Endpoint::parse("COM3"), Endpoint::Serial("COM3".to_string()));
+        assert_eq!(Endpoint::parse("tcp://10.0.0.1:2000"), Endpoint::Tcp("10.0.0.1:2000".to_string()));
+        assert_eq!(Endpoint::parse("unix:///run/modem.sock"), Endpoint::Unix("/run/modem.sock".to_string()));
+        assert_eq!(Endpoint::parse("tcp://10.0.0.1:2000").to_string(), "tcp://10.0.0.1:2000");
+    }
+
+    #[test]
+    fn test_session_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // ser2net-like peer answering two commands
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut command = Vec::new();
+            for answer in ["+CSQ: 11,2\r\n\r\nOK\r\n", "+CME ERROR: 10\r\n"] {
+                command.clear();
+                reader.read_until(b'\r', &mut command).unwrap();
+                writer.write_all(&command).unwrap();
+                writer.write_all(b"\r\n").unwrap();
+                writer.write_all(answer.as_bytes()).unwrap();
+            }
+        });
+
+        let session = ModemSession::open(&Endpoint::parse(&format!("tcp://{}", address)), 115_200).unwrap();
+        let response = session.execute("AT+CSQ?").unwrap();
+        assert_eq!(response.lines, vec!["+CSQ: 11,2"]);
+        assert_eq!(response.result, FinalResult::Ok);
+
+        let response = session.clone().execute("AT+CIMI").unwrap();
+        assert_eq!(response.result, FinalResult::CmeError(10));
+    }
+}
+
+#[cfg(test)]
+mod test_support {
+    use std::time::Duration;
+    use crate::modem_tools::session::ModemSession;
+    use crate::modem_tools::simulator::{Scenario, SimulatedModem};
+
+    /// Session on the simulated L850-GL, answering after 1 ms
+    pub fn simulated_session(script: &str) -> ModemSession {
+        let scenario = Scenario::parse(script).unwrap();
+        ModemSession::start(Box::new(SimulatedModem::new(scenario, Duration::from_millis(1))))
+    }
+}
+
+#[cfg(test)]
+mod simulator_tests {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+    use crate::modem_tools::at_response::{AtError, FinalResult};
+    use crate::modem_tools::modem::{get_modem_info, poll_modem};
+    use crate::modem_tools::supported_modems::FIBOCOM_L850GL;
+    use crate::modem_tools::simulator::{Scenario, SimulatedModem};
+    use crate::tests::test_support::simulated_session;
+
+    #[test]
+    fn test_simulator_answers_poll() {
+        let session = simulated_session("");
+        let info = get_modem_info(poll_modem(&session, &FIBOCOM_L850GL).text);
+        assert_eq!(info.manufacturer.as_deref(), Some("Fibocom"));
+        assert_eq!(info.model.as_deref(), Some("L850"));
+        assert_eq!(info.operator.as_deref(), Some("mt:s"));
+        assert_eq!(info.rsrp, Some(-92));
+        assert_eq!(info.rsrq, Some(-12));
+        assert_eq!(info.sinr, Some(-2));
+        assert_eq!(info.carriers.iter().map(|c| c.pci).collect::<Vec<_>>(), vec![0x62, 0x61, 0x6A]);
+        assert_eq!(info.band.as_deref(), Some("B3@20MHz "));
+        assert_eq!(info.enabled_lte_bands.len(), 19);
+    }
+
+    #[test]
+    fn test_simulator_scenario() {
+        let session = simulated_session("\
+            set rsrp -80          # strong start
+            at 2 handover 0x0063 6300
+            at 2 error AT+XACT? 10
+        ");
+        let info = get_modem_info(poll_modem(&session, &FIBOCOM_L850GL).text);
+        assert_eq!(info.rsrp, Some(-80));
+        assert_eq!(info.carriers[0].pci, 0x62);
+
+        let response = session.execute("AT+CSQ?").unwrap();
+        assert_eq!(response.lines, vec!["+CSQ: 11,2"]);
+        assert_eq!(session.execute("AT+XMCI=1").unwrap().lines[0].split(',').nth(5).unwrap(), "\"0x0063\"");
+        assert_eq!(session.execute("AT+XACT?").unwrap().result, FinalResult::CmeError(10));
+        // Faults are one-shot
+        assert!(session.execute("AT+XACT?").unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_poll_survives_timeout() {
+        let session = simulated_session("timeout AT+COPS?");
+        let poll = poll_modem(&session, &FIBOCOM_L850GL);
+        assert!(!poll.text.contains("AT+COPS?"));
+        let info = poll.parse(&FIBOCOM_L850GL);
+        assert_eq!(info.operator, None);
+        assert_eq!(info.rsrp, Some(-92));
+        let issues: Vec<String> = info.parse_report.issues.iter().map(|issue| issue.to_string()).collect();
+        assert_eq!(issues, vec!["AT+COPS? timed out"]);
+    }
+
+    #[test]
+    fn test_late_answer_after_timeout() {
+        let session = simulated_session("late AT+CSQ?");
+        assert!(matches!(session.execute("AT+CSQ?"), Err(AtError::Timeout(_))));
+        // The late +CSQ answer arrives first and must not be taken for this one
+        let response = session.execute("AT+CGMI").unwrap();
+        assert_eq!(response.lines, vec!["+CGMI: \"Fibocom\""]);
+        assert_eq!(response.result, FinalResult::Ok);
+        assert!(session.execute("AT+CSQ?").unwrap().lines[0].starts_with("+CSQ: "));
+        assert!(session.execute("AT+CGMM").unwrap().lines[0].contains("L850"));
+    }
+
+    #[test]
+    fn test_simulator_band_lock() {
+        let session = simulated_session("");
+        assert!(session.execute("AT+XACT=2,,,103,120").unwrap().is_ok());
+        assert_eq!(session.execute("AT+XACT?").unwrap().lines, vec!["+XACT: 2,1,,103,120"]);
+        assert_eq!(session.execute("AT+BOGUS").unwrap().result, FinalResult::Error);
+    }
+
+    #[test]
+    fn test_simulator_timeout_and_urc() {
+        let scenario = Scenario::parse("at 1 timeout AT+CGMI?\nat 1 urc +CREG: 1").unwrap();
+        let mut modem = SimulatedModem::new(scenario, Duration::from_millis(1));
+        let mut buf = [0u8; 256];
+
+        modem.write_all(b"AT+CSQ?\r").unwrap();
+        let count = modem.read(&mut buf).unwrap();
+        let output = String::from_utf8_lossy(&buf[..count]).to_string();
+        assert!(output.contains("+CREG: 1"));
+        assert!(output.ends_with("OK\r\n"));
+
+        modem.write_all(b"AT+CGMI?\r").unwrap();
+        assert_eq!(modem.read(&mut buf).unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_scenario_errors() {
+        assert!(Scenario::parse("explode now").is_err());
+        assert!(Scenario::parse("at x set rsrp 1").is_err());
+        assert!(Scenario::parse("handover 0x10").is_err());
+    }
+}
+
+#[cfg(test)]
+mod recording_tests {
+    use std::io::{ErrorKind, Read, Write};
+    use std::time::{Duration, Instant};
+    use crate::modem_tools::modem::{get_modem_info, poll_modem};
+    use crate::modem_tools::supported_modems::FIBOCOM_L850GL;
+    use crate::modem_tools::recording::ReplayTransport;
+    use crate::modem_tools::session::ModemSession;
+    use crate::modem_tools::simulator::{Scenario, SimulatedModem};
+    use crate::serial_tools::transport::Endpoint;
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("modem-manager-{}.session", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let scenario = Scenario::parse("set rsrp -101\nat 1 error AT+XACT? 10").unwrap();
+        let live = ModemSession::start(Box::new(SimulatedModem::new(scenario, Duration::from_millis(1))));
+        live.record_to(path).unwrap();
+        let recorded = poll_modem(&live, &FIBOCOM_L850GL).text;
+        drop(live);
+
+        let replay = ModemSession::open(&Endpoint::parse(&format!("replay://{}", path)), 115_200).unwrap();
+        let replayed = poll_modem(&replay, &FIBOCOM_L850GL).text;
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(recorded, replayed);
+        let info = get_modem_info(replayed);
+        assert_eq!(info.rsrp, Some(-101));
+        assert!(info.enabled_lte_bands.is_empty());
+    }
+
+    #[test]
+    fn test_replay_timing_and_end() {
+        let recording = r"# modem-manager session recording
+> 1718000000000 AT+CSQ?
+> 1718000000000 \r
+< 1718000000040 AT+CSQ?\r\r\n+CSQ: 11,2\r\n
+< 1718000000041 \r\nOK\r\n
+< 1718000001000 \r\n+CREG: 1\r\n
+> 1718000002000 AT+CGMI?\r
+> 1718000007000 AT+CSQ?\r
+< 1718000007010 AT+CSQ?\r\r\n+CSQ: 20,2\r\n\r\nOK\r\n
+";
+        let mut replay = ReplayTransport::parse(recording, Duration::from_millis(1));
+        let mut buf = [0u8; 256];
+        // Waits out the recorded delay, up to a second
+        let mut read_next = |replay: &mut ReplayTransport| {
+            for _ in 0..1000 {
+                match replay.read(&mut buf) {
+                    Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+                    result => return result.map(|count| String::from_utf8_lossy(&buf[..count]).to_string()).map_err(|e| e.kind()),
+                }
+            }
+            Err(ErrorKind::TimedOut)
+        };
+
+        let sent = Instant::now();
+        replay.write_all(b"AT+CSQ?").unwrap();
+        replay.write_all(b"\r").unwrap();
+        // Same chunks as read in the field, the first one 40 ms after the command
+        assert_eq!(read_next(&mut replay).unwrap(), "AT+CSQ?\r\r\n+CSQ: 11,2\r\n");
+        assert!(sent.elapsed() >= Duration::from_millis(40));
+        assert_eq!(read_next(&mut replay).unwrap(), "\r\nOK\r\n");
+
+        // The URC recorded before the next command is delivered first, the command itself was never answered
+        replay.write_all(b"AT+CGMI?\r").unwrap();
+        assert_eq!(read_next(&mut replay).unwrap(), "\r\n+CREG: 1\r\n");
+        assert!(matches!(replay.read(&mut [0u8; 16]), Err(e) if e.kind() == ErrorKind::TimedOut));
+
+        assert_eq!(replay.write_all(b"AT+COPS?\r").unwrap_err().kind(), ErrorKind::InvalidData);
+        replay.write_all(b"AT+CSQ?\r").unwrap();
+        assert!(read_next(&mut replay).unwrap().contains("+CSQ: 20,2"));
+        // No wrapping around at the end
+        assert_eq!(read_next(&mut replay), Err(ErrorKind::UnexpectedEof));
+        assert_eq!(replay.write_all(b"AT+CSQ?\r").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use std::process::ExitCode;
+    use clap::Parser;
+    use crate::cli::{run, Cli};
+    use crate::modem_tools::modem::poll_modem;
+    use crate::modem_tools::supported_modems::{ModemProfile, FIBOCOM_L850GL};
+    use crate::tests::test_support::simulated_session;
+
+    fn run_with(args: &[&str]) -> ExitCode {
+        run(Cli::parse_from([&["modem-manager"], args].concat()))
+    }
+
+    #[test]
+    fn test_cli_exit_codes() {
+        assert_eq!(run_with(&["--port", "sim://", "at", "AT+XACT?"]), ExitCode::SUCCESS);
+        assert_eq!(run_with(&["--port", "sim://", "at", "AT+BOGUS"]), ExitCode::from(1));
+        assert_eq!(run_with(&["--port", "sim://", "bands", "set", "--lte", "3,99"]), ExitCode::from(2));
+        // Enabling every band again has to be asked for
+        assert_eq!(run_with(&["--port", "sim://", "bands", "set"]), ExitCode::from(2));
+        assert_eq!(run_with(&["--port", "sim://", "bands", "set", "--all"]), ExitCode::SUCCESS);
+        assert_eq!(run_with(&["info"]), ExitCode::from(2));
+        assert_eq!(run_with(&["--port", "replay:///nonexistent.session", "signal"]), ExitCode::from(3));
+    }
+
+    #[test]
+    fn test_bands_set_generic() {
+        // A generic modem has no band lock command, whatever bands it reports
+        let path = std::env::temp_dir().join(format!("modem-manager-{}-generic.scenario", std::process::id()));
+        std::fs::write(&path, "error AT+FMM?\nreply AT+CGMM EG25-G\n").unwrap();
+        let port = format!("sim://{}", path.display());
+        assert_eq!(run_with(&["--port", &port, "bands", "set", "--lte", "3"]), ExitCode::from(2));
+        assert_eq!(run_with(&["--port", &port, "bands", "set", "--all"]), ExitCode::from(2));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bands_set_all() {
+        // Each run opens its own simulator, so replay what `bands set` and `bands get` send on one session
+        let session = simulated_session("");
+        let profile = &FIBOCOM_L850GL;
+        session.execute(&profile.band_lock_command(&[], &[3], &[]).unwrap()).unwrap();
+        let info = poll_modem(&session, profile).parse(profile);
+        assert_eq!((info.enabled_umts_bands.len(), info.enabled_lte_bands.clone()), (0, vec![3]));
+
+        // What `bands set --all` sends
+        assert!(session.execute(&profile.band_lock_command(&[], &[], &[]).unwrap()).unwrap().is_ok());
+        let info = poll_modem(&session, profile).parse(profile);
+        assert_eq!(info.enabled_umts_bands, [1, 2, 4, 5, 8]);
+        assert!(info.enabled_lte_bands.contains(&3) && info.enabled_lte_bands.contains(&66));
+    }
+
+    #[test]
+    fn test_cli_arguments() {
+        assert!(Cli::try_parse_from(["modem-manager", "-p", "sim://", "bands", "set", "--lte", "3,7,20"]).is_ok());
+        assert!(Cli::try_parse_from(["modem-manager", "bands", "set", "--lte", "3,B7"]).is_err());
+        assert!(Cli::try_parse_from(["modem-manager", "bands", "set", "--all", "--lte", "3"]).is_err());
+        assert!(Cli::try_parse_from(["modem-manager", "at"]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use std::time::Duration;
+    use crate::modem_tools::export::{ExportFormat, SignalReport};
+    use crate::modem_tools::modem::{get_modem_info, poll_modem};
+    use crate::modem_tools::supported_modems::FIBOCOM_L850GL;
+    use crate::modem_tools::session::ModemSession;
+    use crate::modem_tools::simulator::{Scenario, SimulatedModem};
+    use crate::modem_tools::types::ModemInfo;
+
+    fn simulated_info() -> ModemInfo {
+        let modem = SimulatedModem::new(Scenario::default(), Duration::from_millis(10));
+        let session = ModemSession::start(Box::new(modem));
+        get_modem_info(poll_modem(&session, &FIBOCOM_L850GL).text)
+    }
+
+    #[test]
+    fn test_json_field_names() {
+        let info = simulated_info();
+        let json: serde_json::Value = serde_json::from_str(&ExportFormat::Json.serialize(&info).unwrap().unwrap()).unwrap();
+
+        assert_eq!(json["model"], "L850");
+        assert_eq!(json["rsrp_dbm"], -92);
+        assert_eq!(json["sinr_db"], -2);
+        assert_eq!(json["earfcn"], 1500);
+        assert_eq!(json["config_band_modes"], serde_json::json!(["UMTS", "LTE"]));
+        assert_eq!(json["carriers"][0]["pci"], 0x62);
+        assert_eq!(json["carriers"][1]["rsrp_dbm"], -99);
+    }
+
+    #[test]
+    fn test_toml_signal_report() {
+        let info = simulated_info();
+        let toml = ExportFormat::Toml.serialize(&SignalReport::from(&info)).unwrap().unwrap();
+
+        assert!(toml.contains("rsrq_db = -12"));
+        assert_eq!(toml.matches("[[carriers]]").count(), 3);
+        assert!(ExportFormat::Text.serialize(&info).is_none());
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use crate::modem_tools::history::{cell_changes, HistoryFormat, HistorySample, HistoryWriter, SignalHistory};
+    use crate::modem_tools::registration::{NetworkRegistration, Registration, RegistrationStatus};
+    use crate::modem_tools::types::{CarrierInfo, ModemInfo, NrCarrierInfo, NrMode};
+
+    fn sample(timestamp_ms: u64, rsrp: i32) -> HistorySample {
+        let info = ModemInfo {
+            rsrp: Some(rsrp),
+            registration: NetworkRegistration {
+                eps: Some(Registration { status: RegistrationStatus::Roaming, ..Default::default() }),
+                ..Default::default()
+            },
+            band: Some("B3@20MHz B7@10MHz ".to_string()),
+            carriers: vec![CarrierInfo { pci: 98, earfcn: 1500, ..Default::default() }],
+            ..Default::default()
+        };
+        HistorySample::new(&info, timestamp_ms)
+    }
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let mut history = SignalHistory::with_capacity(3);
+        for i in 0..5 {
+            history.push(sample(i, -90 - i as i32)).unwrap();
+        }
+
+        let path = std::env::temp_dir().join(format!("modem-manager-{}-history.csv", std::process::id()));
+        assert_eq!(history.save(path.to_str().unwrap()).unwrap(), 3);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp_ms,rsrp_dbm,rsrq_db,sinr_db,rssi_dbm,csq,band,pci,earfcn,ca,registration");
+        // Measurements the modem did not report are left empty
+        assert_eq!(lines[1], "2,-92,,,,,B3,98,1500,B3@20MHz B7@10MHz,roaming");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_history_umts_and_sa() {
+        // 2937 is a band VIII UARFCN, the same number as an LTE band 7 EARFCN
+        let umts = ModemInfo { mode: Some("UMTS".to_string()), dluarfnc: Some(2937), csq: Some(18), ..Default::default() };
+        let sample = HistorySample::new(&umts, 1);
+        let mut history = SignalHistory::default();
+        history.push(sample).unwrap();
+        let path = std::env::temp_dir().join(format!("modem-manager-{}-umts.csv", std::process::id()));
+        history.save(path.to_str().unwrap()).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        // No PCI or EARFCN on UMTS
+        assert_eq!(csv.lines().nth(1), Some("1,,,,,18,B8,,,,"));
+
+        let sa = ModemInfo {
+            mode: Some("NR".to_string()),
+            nr_mode: NrMode::Sa,
+            nr_carriers: vec![NrCarrierInfo { pci: 500, nr_arfcn: 627264, band: Some(78), ss_rsrp: -95, ss_rsrq: -11, ss_sinr: 12, ..Default::default() }],
+            ..Default::default()
+        };
+        let sample = HistorySample::new(&sa, 2);
+        assert_eq!((sample.rsrp_dbm, sample.rsrq_db, sample.sinr_db), (Some(-95), Some(-11), Some(12)));
+        assert_eq!((sample.band.as_str(), sample.pci, sample.earfcn), ("n78", Some(500), None));
+    }
+
+    #[test]
+    fn test_history_writer_appends() {
+        let path = std::env::temp_dir().join(format!("modem-manager-{}-append.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        for i in 0..2 {
+            // A restart opens the same file again
+            HistoryWriter::create(path).unwrap().write(&sample(i, -90)).unwrap();
+        }
+        let csv = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("timestamp_ms,"));
+        assert!(lines[1].starts_with("0,-90,"));
+        assert!(lines[2].starts_with("1,-90,"));
+    }
+
+    #[test]
+    fn test_cell_changes() {
+        let mut handover = sample(3, -100);
+        handover.pci = Some(99);
+        let mut band_change = sample(4, -101);
+        band_change.earfcn = Some(3050);
+        band_change.band = "B7".to_string();
+        let samples = [sample(1, -95), sample(2, -96), handover, band_change];
+        let refs: Vec<&HistorySample> = samples.iter().collect();
+
+        let changes = cell_changes(&refs);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].to.timestamp_ms, 3);
+        assert_eq!(changes[0].describe(), "PCI 98→99");
+        assert_eq!(changes[1].describe(), "PCI 99→98 EARFCN 1500→3050 B3→B7");
+    }
+
+    #[test]
+    fn test_history_tab_renders() {
+        use ratatui::buffer::Buffer;
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Widget;
+        use crate::display_tools::tui::app_tabs::{AppTabs, SelectedTab};
+
+        let tabs = AppTabs { selected_tab: SelectedTab::History, ..Default::default() };
+        for i in 0..10 {
+            tabs.history.lock().unwrap().push(sample(i * 3000, -90 - i as i32)).unwrap();
+        }
+        let area = Rect::new(0, 0, 100, 40);
+        let mut buf = Buffer::empty(area);
+        tabs.render(area, &mut buf);
+
+        let screen: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("RSRP -99dBm"));
+        assert!(screen.contains("10 samples"));
+    }
+
+    #[test]
+    fn test_history_jsonl() {
+        assert_eq!(HistoryFormat::from_path("drive.jsonl"), HistoryFormat::Jsonl);
+        assert_eq!(HistoryFormat::from_path("drive.csv"), HistoryFormat::Csv);
+
+        let mut history = SignalHistory::default();
+        history.push(sample(1, -95)).unwrap();
+        history.push(sample(2, -96)).unwrap();
+
+        let path = std::env::temp_dir().join(format!("modem-manager-{}-history.jsonl", std::process::id()));
+        history.save(path.to_str().unwrap()).unwrap();
+        let jsonl = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let second: serde_json::Value = serde_json::from_str(jsonl.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(second["timestamp_ms"], 2);
+        assert_eq!(second["rsrp_dbm"], -96);
+        assert_eq!(second["pci"], 98);
+        assert_eq!(second["registration"], "roaming");
+    }
+}
+
+
+#[cfg(test)]
+mod profile_tests {
+    use crate::modem_tools::converters::{get_band_nr, nr_channel};
+    use crate::modem_tools::modem::{poll_modem, ModemPoll};
+    use crate::modem_tools::parse_report::ParseReport;
+    use crate::modem_tools::supported_modems::{detect_modem, BandCapabilities, FIBOCOM_FM350GL, FIBOCOM_L850GL, FIBOCOM_L860GL, GENERIC_3GPP, ModemProfile};
+    use crate::modem_tools::types::NrMode;
+    use crate::tests::test_support::simulated_session;
+
+    #[test]
+    fn test_detect_profile() {
+        let modem = detect_modem(&simulated_session("")).unwrap();
+        assert_eq!(modem.profile.name(), "Fibocom L850-GL");
+        assert_eq!(modem.identity.manufacturer, "Fibocom");
+        assert_eq!(modem.identity.model, "L850");
+        assert_eq!(modem.identity.revision, "18500.5001.00.05.27.30_5001.05.001.035");
+        assert_eq!(modem.bands, BandCapabilities::declared(&FIBOCOM_L850GL));
+    }
+
+    #[test]
+    fn test_detect_unknown_modem() {
+        // A Sierra Wireless module: no Fibocom commands, identity from ATI and +CGMM
+        let unknown = simulated_session("
+            error AT+FMM?
+            timeout AT+GTPKGVER?   # not answered at all, still not a Fibocom
+            error AT+CGMI
+            reply ATI Manufacturer: Sierra Wireless, Incorporated
+            reply AT+CGMM EM7455
+        ");
+        let modem = detect_modem(&unknown).unwrap();
+        assert!(modem.generic);
+        assert_eq!(modem.profile.name(), "Generic 3GPP");
+        assert_eq!(modem.identity.manufacturer, "Sierra Wireless, Incorporated");
+        assert_eq!(modem.identity.model, "EM7455");
+        assert_eq!(modem.identity.describe(), "Sierra Wireless, Incorporated EM7455 (revision unknown)");
+        assert_eq!(modem.bands, BandCapabilities::default());
+    }
+
+    #[test]
+    fn test_probed_bands() {
+        // Regional SKU with fewer bands than the model supports
+        let session = simulated_session("reply AT+XACT=? +XACT: (0-6),(0-2),0,1,5,101,103,107,120");
+        let modem = detect_modem(&session).unwrap();
+        assert_eq!(modem.bands, BandCapabilities { umts: vec![1, 5], lte: vec![1, 3, 7, 20], nr: vec![] });
+
+        let fm350 = FIBOCOM_FM350GL.parse_supported_bands("+GTACT: (1,2,4,14,16,17,20),(2,3,6),(2,3,6),1,101,103,5078\r\n").unwrap();
+        assert_eq!(fm350, BandCapabilities { umts: vec![1], lte: vec![1, 3], nr: vec![78] });
+        assert!(FIBOCOM_L850GL.parse_supported_bands("+XACT: (0-6),(0-2),0").is_none());
+    }
+
+    // Quectel EC25: identity commands answer without a prefix, +CEREG without AcT
+    static QUECTEL_TEST_STRING: &str = "AT+CGMI\r\nQuectel\r\nOK\r\n\
+    AT+CGMM\r\nEC25\r\nOK\r\n\
+    AT+CGMR\r\nRevision: EC25EFAR06A06M4G\r\nOK\r\n\
+    AT+CGSN\r\n866758042178412\r\nOK\r\n\
+    AT+CIMI\r\n262011234567890\r\nOK\r\n\
+    AT+COPS?\r\n+COPS: 0\r\nOK\r\n\
+    AT+CSQ\r\n+CSQ: 17,99\r\nOK\r\n\
+    AT+CESQ\r\n+CESQ: 99,99,255,255,22,47\r\nOK\r\n\
+    AT+CREG?\r\n+CREG: 0,1\r\nOK\r\n\
+    AT+CGREG?\r\n+CGREG: 0,1\r\nOK\r\n\
+    AT+CEREG?\r\n+CEREG: 0,1\r\nOK\r\n\
+    AT+C5GREG?\r\nERROR\r\n\
+    AT+CGDCONT?\r\n+CGDCONT: 1,\"IP\",\"internet\",\"0.0.0.0\",0,0\r\n+CGDCONT: 2,\"IPV4V6\",\"ims\",\"0.0.0.0\",0,0\r\nOK\r\n\
+    AT+CGPADDR\r\n+CGPADDR: 1,\"10.23.4.5\"\r\n+CGPADDR: 2,\"10.64.0.9\"\r\nOK\r\n";
+
+    #[test]
+    fn test_generic_fixture() {
+        let poll = ModemPoll { text: QUECTEL_TEST_STRING.to_string(), unanswered: ParseReport::default() };
+        let info = poll.parse(&GENERIC_3GPP);
+        assert_eq!((info.manufacturer.as_deref(), info.model.as_deref()), (Some("Quectel"), Some("EC25")));
+        assert_eq!(info.fw_version.as_deref(), Some("EC25EFAR06A06M4G"));
+        assert_eq!(info.imei.as_deref(), Some("866758042178412"));
+        assert_eq!(info.imsi.as_deref(), Some("262011234567890"));
+        assert_eq!((info.csq, info.rssi), (Some(17), Some(-79)));
+        assert_eq!((info.rsrp, info.rsrq), (Some(-94), Some(-9)));
+        // Neither +COPS nor +CEREG tell the access technology
+        assert_eq!(info.mode.as_deref(), Some("Registered"));
+        assert_eq!((info.apn.as_deref(), info.ip.as_deref()), (Some("internet"), Some("10.23.4.5")));
+        assert_eq!(info.operator, None);
+        // +C5GREG is not supported by an LTE-only modem, that is no parse issue
+        assert!(info.parse_report.issues.is_empty(), "{:?}", info.parse_report.issues);
+        assert_eq!(GENERIC_3GPP.band_lock_command(&[], &[3], &[]), None);
+    }
+
+    #[test]
+    fn test_generic_prefixed_unquoted_identity() {
+        let info = GENERIC_3GPP.parse_info("AT+CGMI\r\n+CGMI: Quectel\r\nOK\r\n\
+            AT+CGMM\r\n+CGMM: \"EC25\"\r\nOK\r\n\
+            AT+CGSN\r\n+CGSN: 866758042178412\r\nOK\r\n".to_string());
+        assert_eq!((info.manufacturer.as_deref(), info.model.as_deref()), (Some("Quectel"), Some("EC25")));
+        assert_eq!(info.imei.as_deref(), Some("866758042178412"));
+        assert!(info.parse_report.issues.is_empty(), "{:?}", info.parse_report.issues);
+    }
+
+    #[test]
+    fn test_generic_registration_mode() {
+        for (act, mode) in [(3, "GSM"), (10, "LTE"), (12, "NR"), (13, "EN-DC")] {
+            let info = GENERIC_3GPP.parse_info(format!("AT+CEREG?\r\n+CEREG: 2,1,\"4E91\",\"00009C03\",{}\r\nOK\r\n", act));
+            assert_eq!(info.mode.as_deref(), Some(mode), "AcT {}", act);
+        }
+    }
+
+    #[test]
+    fn test_generic_simulated() {
+        // An LTE-only modem may not even answer +C5GREG
+        let session = simulated_session("error AT+FMM?\nreply AT+CGMM EG25-G\ntimeout AT+C5GREG?");
+        let modem = detect_modem(&session).unwrap();
+        let profile = modem.profile;
+        assert!(modem.generic);
+        let info = poll_modem(&session, profile).parse(profile);
+        assert_eq!(info.model.as_deref(), Some("EG25-G"));
+        assert_eq!(info.operator.as_deref(), Some("mt:s"));
+        assert_eq!(info.mode.as_deref(), Some("LTE"));
+        assert_eq!((info.rsrp, info.rsrq), (Some(-92), Some(-12)));
+        assert_eq!(info.ip.as_deref(), Some("10.179.248.170"));
+        assert!(info.carriers.is_empty());
+        assert!(info.parse_report.issues.is_empty(), "{:?}", info.parse_report.issues);
+    }
+
+    // Fibocom L860-GL (XMM7560) with three aggregated carriers
+    static L860_TEST_STRING: &str = "+CGMI: \"Fibocom\"\r\n\r\nOK\r\n\
+    +FMM: \"L860-GL-16 LTE Module\",\"L860\"\r\n\r\nOK\r\n\
+    +GTPKGVER: \"18600.5001.00.01.01.09\"\r\n\r\nOK\r\n\
+    +COPS: 0,0,\"Telekom.de\",7\r\n\r\nOK\r\n\
+    +CSQ: 24,99\r\n\r\nOK\r\n\
+    +XCCINFO: 0,262,01,\"0131F20A\",3,103,\"FFFF\",1,\"FF\",\"A4B2\",0,0,0,0,0,0,0,0\r\n\r\n\
+    +XLEC: 0,3,5,5,3,BAND_LTE_3,7,20\r\n\r\n\
+    +XMCI: 4,262,01,\"0xA4B2\",\"0x0131F20A\",\"0x00D2\",\"0x00000514\",\"0x000049F4\",\"0xFFFFFFFF\",58,22,24,\"0x00000005\",\"0x00000000\"\r\n\r\n\
+    +XMCI: 5,000,000,\"0xFFFE\",\"0xFFFFFFFF\",\"0x00D2\",\"0x00000C1C\",\"0xFFFFFFFF\",\"0xFFFFFFFF\",52,20,255,\"0x7FFFFFFF\",\"0x00000000\"\r\n\r\n\
+    +XMCI: 5,000,000,\"0xFFFE\",\"0xFFFFFFFF\",\"0x0133\",\"0x0000189C\",\"0xFFFFFFFF\",\"0xFFFFFFFF\",47,16,255,\"0x7FFFFFFF\",\"0x00000000\"\r\n\r\n\
+    OK\r\n\r\n\
+    +XACT: 4,2,1,1,8,101,103,107,108,120,128,132,138,141,142,143\r\n\r\nOK\r\n";
+
+    #[test]
+    fn test_l860_fixture() {
+        let info = FIBOCOM_L860GL.parse_info(L860_TEST_STRING.to_string());
+        assert_eq!(info.model.as_deref(), Some("L860"));
+        assert_eq!(info.fw_version.as_deref(), Some("18600.5001.00.01.01.09"));
+        assert_eq!(info.band.as_deref(), Some("B3@20MHz B7@20MHz B20@10MHz "));
+        assert_eq!(info.rsrp, Some(-83));
+        assert_eq!(info.sinr, Some(12));
+        assert_eq!(info.carriers.iter().map(|c| c.earfcn).collect::<Vec<_>>(), vec![1300, 3100, 6300]);
+        // Secondary carriers are matched to the +XLEC bands
+        assert_eq!(info.carriers.iter().map(|c| c.bandwidth).collect::<Vec<_>>(), vec![Some(20.0), Some(20.0), Some(10.0)]);
+        let carriers = info.display_carrier_info();
+        assert!(carriers.contains("B3  ( 1300) FDD, DL 1815.0 MHz, UL 1720.0 MHz BW:  20MHz"));
+        assert!(carriers.contains("B20 ( 6300) FDD, DL 806.0 MHz, UL 847.0 MHz BW:  10MHz"));
+        // Neighbours report 0x7FFFFFFF
+        assert_eq!(info.carriers.iter().map(|c| c.timing_advance).collect::<Vec<_>>(), vec![Some(5), None, None]);
+        assert_eq!(info.distance, Some(390.0));
+        assert!(info.display_signal_info().contains("Distance:             390m (351-429m)"));
+        assert_eq!(info.enabled_umts_bands, vec![1, 8]);
+        assert_eq!(info.enabled_lte_bands, vec![1, 3, 7, 8, 20, 28, 32, 38, 41, 42, 43]);
+    }
+
+    #[test]
+    fn test_detect_l860() {
+        let session = simulated_session(r#"reply AT+FMM? +FMM: "L860-GL-16 LTE Module","L860""#);
+        let profile = detect_modem(&session).unwrap().profile;
+        assert_eq!(profile.name(), "Fibocom L860-GL");
+        assert!(profile.supported_lte_bands().contains(&42));
+        assert!(profile.lte_4x4_mimo_bands().contains(&7));
+        assert!(FIBOCOM_L850GL.lte_4x4_mimo_bands().is_empty());
+        assert_eq!(profile.band_lock_command(&[], &[41, 42], &[]), Some("AT+XACT=2,,,141,142".to_string()));
+    }
+
+    // Fibocom FM350-GL attached in EN-DC: LTE anchor on B3, NR secondary cell on n78
+    static FM350_TEST_STRING: &str = "+CGMI: \"Fibocom Wireless Inc.\"\r\n\r\nOK\r\n\
+    +FMM: \"FM350-GL-00 5G Module\",\"FM350-GL\"\r\n\r\nOK\r\n\
+    +GTPKGVER: \"81600.0000.00.29.22.02_GC\"\r\n\r\nOK\r\n\
+    +COPS: 0,0,\"Vodafone.de\",13\r\n\r\nOK\r\n\
+    +CSQ: 20,99\r\n\r\nOK\r\n\
+    +GTCCINFO: \r\n\
+    LTE service cell:\r\n\
+    1,4,262,02,B17C,01A2D50F,1300,D2,103,5,60,50,58,30\r\n\
+    LTE neighbor cell:\r\n\
+    2,4,262,02,B17C,0,1300,61,103,5,,,45,20\r\n\
+    NR service cell:\r\n\
+    1,9,262,02,B17C,0,627264,1F5,5078,100,70,,70,80\r\n\r\nOK\r\n\
+    +GTCAINFO: \r\n\
+    PCC:103,D2,1300,5,5\r\n\
+    SCC1:2,0,5078,1F5,627264,100,100\r\n\
+    SCC2:0,0,107,33,3100,5,5\r\n\r\nOK\r\n\
+    +GTACT: 20,6,3,1,8,101,103,107,120,5001,5028,5078\r\n\r\nOK\r\n";
+
+    #[test]
+    fn test_fm350_fixture() {
+        let info = FIBOCOM_FM350GL.parse_info(FM350_TEST_STRING.to_string());
+        assert_eq!(info.model.as_deref(), Some("FM350-GL"));
+        assert_eq!(info.mode.as_deref(), Some("EN-DC"));
+        assert_eq!(info.nr_mode, NrMode::EnDc);
+        assert_eq!((info.rsrp, info.rsrq, info.sinr), (Some(-83), Some(-5), Some(6)));
+        assert_eq!(info.carriers.iter().map(|c| c.pci).collect::<Vec<_>>(), vec![0xD2, 0x61]);
+        assert_eq!(info.band.as_deref(), Some("B3@20MHz n78@100MHz"));
+
+        let nr = &info.nr_carriers[0];
+        assert_eq!((nr.pci, nr.nr_arfcn, nr.band, nr.bandwidth), (501, 627264, Some(78), 100));
+        assert_eq!((nr.ss_rsrp, nr.ss_rsrq, nr.ss_sinr), (-87, -3, 11));
+
+        assert_eq!(info.enabled_umts_bands, vec![1, 8]);
+        assert_eq!(info.enabled_lte_bands, vec![1, 3, 7, 20]);
+        assert_eq!(info.enabled_nr_bands, vec![1, 28, 78]);
+        assert!(info.display_carrier_info().contains("5G NSA (EN-DC)"));
+        assert!(info.display_carrier_info().contains("n78  (627264) TDD, 3408.96 MHz BW: 100MHz"));
+        assert_eq!(info.carriers[0].bandwidth, Some(20.0));
+        // ECI 0x01A2D50F
+        assert_eq!((info.carriers[0].enb_id, info.carriers[0].local_cell_id), (Some(107221), Some(15)));
+        assert!(info.display_carrier_info().contains("eNB: 107221-15 PCI:  210"));
+        assert_eq!((info.carriers[1].enb_id, info.carriers[1].local_cell_id), (None, None));
+    }
+
+    #[test]
+    fn test_fm350_standalone() {
+        let mut info = FIBOCOM_FM350GL.parse_info("+COPS: 0,0,\"T-Mobile\",11\r\n\
+            +GTCCINFO: \r\nNR service cell:\r\n1,9,310,260,1A2B,12345678A,520110,2A,5041,100,60,,80,75\r\n\r\nOK\r\n".to_string());
+        assert_eq!(info.nr_carriers[0].gnb_id, None);
+        info.decode_nr_cell_ids(24);
+        assert_eq!((info.nr_carriers[0].gnb_id, info.nr_carriers[0].local_cell_id), (Some(1193046), Some(1930)));
+        assert!(info.display_carrier_info().contains("gNB: 1193046-1930"));
+        info.decode_nr_cell_ids(22);
+        assert_eq!((info.nr_carriers[0].gnb_id, info.nr_carriers[0].local_cell_id), (Some(298261), Some(10122)));
+        assert_eq!(info.nr_mode, NrMode::Sa);
+        assert_eq!(info.nr_carriers[0].band, Some(41));
+        assert!(info.carriers.is_empty());
+        assert!(info.display_signal_info().contains("(n41 TDD, 2600.55 MHz)"));
+    }
+
+    #[test]
+    fn test_fm350_umts_channel() {
+        let info = FIBOCOM_FM350GL.parse_info("+COPS: 0,0,\"Telekom.de\",2\r\n\
+            +GTCCINFO: \r\nWCDMA service cell:\r\n1,2,262,01,1A2B,00AB12CD,10700,1F,1,,,,,\r\n\r\nOK\r\n".to_string());
+        assert_eq!(info.mode.as_deref(), Some("UMTS"));
+        assert_eq!((info.dluarfnc, info.band.as_deref()), (Some(10700), Some("B1")));
+        assert!(info.display_signal_info().contains("(B1 DL 2140.0 MHz)"));
+    }
+
+    #[test]
+    fn test_fm350_detection_and_band_lock() {
+        let session = simulated_session(r#"reply AT+FMM? +FMM: "FM350-GL-00 5G Module","FM350-GL""#);
+        let profile = detect_modem(&session).unwrap().profile;
+        assert_eq!(profile.name(), "Fibocom FM350-GL");
+        assert!(profile.supported_nr_bands().contains(&78));
+        assert!(FIBOCOM_L850GL.supported_nr_bands().is_empty());
+
+        assert_eq!(profile.band_lock_command(&[], &[3, 7], &[78]), Some("AT+GTACT=17,,,103,107,5078".to_string()));
+        assert_eq!(profile.band_lock_command(&[1], &[], &[]), Some("AT+GTACT=1,,,1".to_string()));
+        assert_eq!(profile.band_lock_command(&[], &[], &[]), Some("AT+GTACT=20".to_string()));
+    }
+
+    #[test]
+    fn test_nr_arfcn_band() {
+        assert_eq!(get_band_nr(368500), Some(3));
+        assert_eq!(get_band_nr(500000), Some(41));
+        assert_eq!(get_band_nr(2087499), Some(257));
+        assert_eq!(get_band_nr(100), None);
+        // n78 lies inside n77 and n1 inside n66
+        assert_eq!(get_band_nr(627264), None);
+        assert_eq!(get_band_nr(428000), None);
+        assert_eq!(nr_channel(620000, None).map(|channel| channel.frequency_mhz), Some(3300.0));
+    }
+
+    #[test]
+    fn test_nr_overlapping_bands() {
+        let bands = |nr_arfcn: i32, reported: Option<usize>| nr_channel(nr_arfcn, reported).unwrap().bands;
+        // 2655 MHz
+        assert_eq!(bands(531000, None), [7, 41]);
+        assert_eq!(bands(531000, Some(7)), [7]);
+        assert_eq!(bands(531000, Some(41)), [41]);
+        // A reported band that does not cover the channel is not trusted
+        assert_eq!(bands(531000, Some(78)), [7, 41]);
+        // 3600 MHz
+        assert_eq!(bands(640000, None), [48, 77, 78]);
+        assert_eq!(bands(640000, Some(48)), [48]);
+        // 800 MHz
+        assert_eq!(bands(160000, None), [20, 28]);
+        assert_eq!(bands(153000, Some(14)), [14]);
+        // 27600 MHz
+        assert_eq!(bands(2072500, None), [257, 261]);
+        assert_eq!(nr_channel(531000, None).unwrap().to_string(), "n7/n41 2655 MHz");
+        assert_eq!(nr_channel(640000, None).unwrap().to_string(), "n48/n77/n78 TDD, 3600 MHz");
+    }
+
+    #[test]
+    fn test_profile_band_lock_command() {
+        assert_eq!(FIBOCOM_L850GL.band_lock_command(&[1, 8], &[3, 7, 20], &[]), Some("AT+XACT=4,2,,1,8,103,107,120".to_string()));
+        // No bands is every band, which `bands set` only sends with `--all`
+        assert_eq!(FIBOCOM_L850GL.band_lock_command(&[], &[], &[]), Some("AT+XACT=0,0,,0".to_string()));
+        assert!(FIBOCOM_L850GL.supported_lte_bands().contains(&66));
+    }
+}
+
+#[cfg(test)]
+mod converters_tests {
+    use crate::modem_tools::converters::{get_band_lte, get_band_umts, lte_channel, nr_channel, parse_timing_advance, split_eci, split_nci, timing_advance_distance, umts_channel, DistanceEstimate, Duplex, Link, LteChannel, NrChannel, TimingAdvanceRat, UmtsChannel};
+
+    fn channel(earfcn: i32) -> LteChannel {
+        lte_channel(earfcn).unwrap_or_else(|| panic!("EARFCN {} has no band", earfcn))
+    }
+
+    #[test]
+    fn test_lte_fdd_band_edges() {
+        assert_eq!(channel(0), LteChannel { band: 1, duplex: Duplex::Fdd, link: Link::Downlink, dl_mhz: 2110.0, ul_mhz: Some(1920.0) });
+        assert_eq!((channel(599).band, channel(599).dl_mhz, channel(599).ul_mhz), (1, 2169.9, Some(1979.9)));
+        assert_eq!((channel(600).band, channel(600).dl_mhz), (2, 1930.0));
+        assert_eq!((channel(1949).band, channel(1950).band), (3, 4));
+        assert_eq!((channel(6449).band, channel(6449).dl_mhz), (20, 820.9));
+        assert_eq!((channel(10359).band, channel(10359).dl_mhz), (32, 1495.9));
+        // Gaps between the bands
+        assert_eq!(lte_channel(-1), None);
+        assert_eq!(lte_channel(5000), None);
+        assert_eq!(lte_channel(65535), None);
+        assert_eq!(lte_channel(70646), None);
+    }
+
+    #[test]
+    fn test_lte_uplink_earfcn() {
+        assert_eq!(channel(18000), LteChannel { band: 1, duplex: Duplex::Fdd, link: Link::Uplink, dl_mhz: 2110.0, ul_mhz: Some(1920.0) });
+        assert_eq!((channel(19949).band, channel(19949).ul_mhz, channel(19949).dl_mhz), (3, Some(1784.9), 1879.9));
+        assert_eq!((channel(133471).band, channel(133471).ul_mhz, channel(133471).dl_mhz), (71, Some(697.9), 651.9));
+        assert_eq!(lte_channel(134282), None);
+    }
+
+    #[test]
+    fn test_lte_tdd_and_high_bands() {
+        assert_eq!(channel(36000), LteChannel { band: 33, duplex: Duplex::Tdd, link: Link::Downlink, dl_mhz: 1900.0, ul_mhz: Some(1900.0) });
+        assert_eq!((channel(39649).band, channel(39649).dl_mhz), (40, 2399.9));
+        assert_eq!((channel(39650).band, channel(39650).dl_mhz), (41, 2496.0));
+        assert_eq!((channel(41589).band, channel(41590).band), (41, 42));
+        assert_eq!((channel(43590).band, channel(43590).dl_mhz), (43, 3600.0));
+        assert_eq!((channel(46790).band, channel(55240).band, channel(56739).band), (46, 48, 48));
+        assert_eq!((channel(65536).band, channel(65536).ul_mhz), (65, Some(1920.0)));
+        assert_eq!((channel(66436).band, channel(66436).ul_mhz), (66, Some(1710.0)));
+        // B66 has 20 MHz more downlink than uplink
+        assert_eq!((channel(67335).dl_mhz, channel(67335).ul_mhz), (2199.9, None));
+        assert_eq!((channel(68586).band, channel(68586).dl_mhz, channel(68586).ul_mhz), (71, 617.0, Some(663.0)));
+    }
+
+    #[test]
+    fn test_lte_supplemental_downlink() {
+        assert_eq!(channel(9660), LteChannel { band: 29, duplex: Duplex::Sdl, link: Link::Downlink, dl_mhz: 717.0, ul_mhz: None });
+        assert_eq!(channel(67336).duplex, Duplex::Sdl);
+    }
+
+    #[test]
+    fn test_lte_labels() {
+        assert_eq!(get_band_lte(3050), "B7");
+        assert_eq!(get_band_lte(66986), "B66");
+        assert_eq!(get_band_lte(65535), "Unknown");
+        assert_eq!(channel(1500).to_string(), "B3 FDD, DL 1835.0 MHz, UL 1740.0 MHz");
+        assert_eq!(channel(38000).to_string(), "B38 TDD, 2595.0 MHz");
+        assert_eq!(channel(9700).to_string(), "B29 SDL, 721.0 MHz");
+    }
+
+    #[test]
+    fn test_umts_channels() {
+        assert_eq!(umts_channel(10700), Some(UmtsChannel { band: 1, link: Link::Downlink, frequency_mhz: 2140.0 }));
+        assert_eq!(umts_channel(10562).map(|c| c.frequency_mhz), Some(2112.4));
+        assert_eq!(umts_channel(10838).map(|c| c.frequency_mhz), Some(2167.6));
+        assert_eq!(umts_channel(9612), Some(UmtsChannel { band: 1, link: Link::Uplink, frequency_mhz: 1922.4 }));
+        assert_eq!(umts_channel(3088).map(|c| (c.band, c.frequency_mhz)), Some((8, 957.6)));
+        assert_eq!(umts_channel(4512).map(|c| (c.band, c.frequency_mhz)), Some((20, 793.4)));
+        // B3 downlink and B4 uplink share numbers, the downlink wins
+        assert_eq!(umts_channel(1513).map(|c| (c.band, c.link)), Some((3, Link::Downlink)));
+        // B6 is a subset of B5
+        assert_eq!(umts_channel(4400).map(|c| (c.band, c.frequency_mhz)), Some((5, 880.0)));
+        assert_eq!(get_band_umts(0), "Unknown");
+        assert_eq!(get_band_umts(5500), "Unknown");
+        assert_eq!(umts_channel(5200).map(|c| (c.band, c.frequency_mhz)), Some((25, 1950.0)));
+        assert_eq!(umts_channel(5800).map(|c| (c.band, c.frequency_mhz)), Some((26, 869.0)));
+        assert_eq!(umts_channel(5600).map(|c| (c.band, c.link)), Some((26, Link::Uplink)));
+    }
+
+    #[test]
+    fn test_umts_additional_channels() {
+        assert_eq!(umts_channel(412).map(|c| (c.band, c.frequency_mhz)), Some((2, 1932.5)));
+        assert_eq!(umts_channel(12).map(|c| (c.band, c.link, c.frequency_mhz)), Some((2, Link::Uplink, 1852.5)));
+        assert_eq!(umts_channel(1887).map(|c| (c.band, c.frequency_mhz)), Some((4, 2112.5)));
+        assert_eq!(umts_channel(787).map(|c| (c.band, c.frequency_mhz)), Some((19, 877.5)));
+        assert_eq!(umts_channel(3932).map(|c| (c.band, c.frequency_mhz)), Some((12, 731.5)));
+        assert_eq!(umts_channel(4092).map(|c| (c.band, c.frequency_mhz)), Some((13, 753.5)));
+        assert_eq!(umts_channel(3942).map(|c| (c.band, c.link, c.frequency_mhz)), Some((14, Link::Uplink, 790.5)));
+        assert_eq!(umts_channel(6592).map(|c| (c.band, c.frequency_mhz)), Some((25, 1992.5)));
+        assert_eq!(umts_channel(6067).map(|c| (c.band, c.link, c.frequency_mhz)), Some((25, Link::Uplink, 1852.5)));
+        // Inside the general band XXVI range, the additional channel is the exact one
+        assert_eq!(umts_channel(5767).map(|c| (c.band, c.frequency_mhz)), Some((26, 862.5)));
+        assert_eq!(umts_channel(10700).unwrap().to_string(), "B1 DL 2140.0 MHz");
+        assert_eq!(umts_channel(9612).unwrap().to_string(), "B1 UL 1922.4 MHz");
+    }
+
+    #[test]
+    fn test_nr_global_raster() {
+        assert_eq!(nr_channel(428000, Some(1)), Some(NrChannel { bands: vec![1], duplex: Some(Duplex::Fdd), frequency_mhz: 2140.0, raster_khz: 5 }));
+        assert_eq!(nr_channel(599999, None).map(|c| (c.frequency_mhz, c.raster_khz)), Some((2999.995, 5)));
+        assert_eq!(nr_channel(600000, None).map(|c| (c.frequency_mhz, c.raster_khz)), Some((3000.0, 15)));
+        assert_eq!(nr_channel(2016666, None).map(|c| c.frequency_mhz), Some(24249.99));
+        assert_eq!(nr_channel(2016667, None).map(|c| (c.frequency_mhz, c.raster_khz)), Some((24250.08, 60)));
+        assert_eq!(nr_channel(3279165, None).map(|c| c.frequency_mhz), Some(99999.96));
+        assert_eq!(nr_channel(3279166, None), None);
+        assert_eq!(nr_channel(-1, None), None);
+    }
+
+    #[test]
+    fn test_cell_identity() {
+        assert_eq!(split_eci(0x00009C03), Some((156, 3)));
+        assert_eq!(split_eci(0x0FFFFFFF), Some((0xFFFFF, 0xFF)));
+        // Unknown cell: 0, or 0xFFFFFFFF read as -1
+        assert_eq!(split_eci(0), None);
+        assert_eq!(split_eci(-1), None);
+        assert_eq!(split_eci(0x10000000), None);
+        assert_eq!(split_nci(0x12345678A, 32), Some((0x12345678, 0xA)));
+        assert_eq!(split_nci(0xFFFFFFFFF, 22), Some((0x3FFFFF, 0x3FFF)));
+        assert_eq!(split_nci(0x12345678A, 21), None);
+        assert_eq!(split_nci(0x1000000000, 24), None);
+    }
+
+    #[test]
+    fn test_timing_advance_distance() {
+        assert_eq!(parse_timing_advance("0x00000003"), Some(3));
+        assert_eq!(parse_timing_advance("0x7FFFFFFF"), None);
+        assert_eq!(parse_timing_advance("0xFFFFFFFF"), None);
+        assert_eq!(parse_timing_advance(""), None);
+
+        // 16 Ts round trip, 78.07 m
+        let lte = |ta| timing_advance_distance(TimingAdvanceRat::Lte, ta);
+        assert_eq!(lte(3), Some(DistanceEstimate { distance: 234.0, min: 195.0, max: 273.0 }));
+        assert_eq!(lte(0), Some(DistanceEstimate { distance: 0.0, min: 0.0, max: 39.0 }));
+        assert_eq!(lte(1282).map(|estimate| estimate.distance), Some(100087.0));
+        assert_eq!(lte(1283), None);
+        assert_eq!(lte(3).unwrap().to_string(), "234m (195-273m)");
+
+        // One bit period round trip, 553.5 m
+        let gsm = |ta| timing_advance_distance(TimingAdvanceRat::Gsm, ta);
+        assert_eq!(gsm(2), Some(DistanceEstimate { distance: 1107.0, min: 830.0, max: 1384.0 }));
+        assert_eq!(gsm(63).map(|estimate| estimate.distance), Some(34868.0));
+        assert_eq!(gsm(64), None);
+    }
+
+    #[test]
+    fn test_nr_channel_labels() {
+        assert_eq!(nr_channel(627264, Some(78)).unwrap().to_string(), "n78 TDD, 3408.96 MHz");
+        assert_eq!(nr_channel(428000, None).unwrap().to_string(), "n1/n66 FDD, 2140 MHz");
+        assert_eq!(nr_channel(143500, None).unwrap().to_string(), "n29 SDL, 717.5 MHz");
+        assert_eq!(nr_channel(100, None).unwrap().to_string(), "0.5 MHz");
+    }
+}
+
+#[cfg(test)]
+mod plmn_tests {
+    use crate::modem_tools::modem::get_modem_info;
+    use crate::modem_tools::plmn::{Plmn, Roaming};
+
+    fn code(plmn: Option<Plmn>) -> Option<(String, String, Option<&'static str>)> {
+        plmn.map(|plmn| (plmn.mcc, plmn.mnc, plmn.operator))
+    }
+
+    #[test]
+    fn test_imsi_mnc_length() {
+        assert_eq!(code(Plmn::from_imsi("220033400995562")), Some(("220".into(), "03".into(), Some("mt:s"))));
+        // North America only has 3 digit MNCs
+        assert_eq!(code(Plmn::from_imsi("310410123456789")), Some(("310".into(), "410".into(), Some("AT&T"))));
+        assert_eq!(code(Plmn::from_imsi("302999123456789")), Some(("302".into(), "999".into(), None)));
+        // India mixes both
+        assert_eq!(code(Plmn::from_imsi("405840123456789")), Some(("405".into(), "840".into(), Some("Jio"))));
+        assert_eq!(code(Plmn::from_imsi("404451234567890")), Some(("404".into(), "45".into(), Some("Airtel"))));
+        assert_eq!(Plmn::from_imsi("22003"), None);
+        assert_eq!(Plmn::from_imsi(""), None);
+    }
+
+    #[test]
+    fn test_numeric_operator() {
+        let plmn = Plmn::from_numeric("26202").unwrap();
+        assert_eq!(plmn.to_string(), "262 02 Vodafone, Germany");
+        assert_eq!(Plmn::from_numeric("310260").unwrap().to_string(), "310 260 T-Mobile, United States");
+        assert_eq!(Plmn::from_numeric("20899").unwrap().to_string(), "208 99 France");
+        assert_eq!(Plmn::from_numeric("99999").unwrap().to_string(), "999 99");
+        assert_eq!(Plmn::from_numeric("mt:s"), None);
+        // +XMCI drops the leading zero
+        assert_eq!(Plmn::new("220", "3").unwrap().mnc, "03");
+    }
+
+    #[test]
+    fn test_roaming() {
+        let home = Plmn::new("262", "03");
+        let between = |mcc, mnc| Roaming::between(home.as_ref(), Plmn::new(mcc, mnc).as_ref());
+        assert_eq!(between("262", "03"), Roaming::Home);
+        assert_eq!(between("262", "07"), Roaming::Home);
+        assert_eq!(between("262", "02"), Roaming::National);
+        assert_eq!(between("220", "03"), Roaming::International);
+        assert_eq!(Roaming::between(Plmn::new("404", "45").as_ref(), Plmn::new("405", "840").as_ref()), Roaming::National);
+        assert_eq!(Roaming::between(None, home.as_ref()), Roaming::Unknown);
+    }
+
+    #[test]
+    fn test_roaming_from_modem_info() {
+        let info = get_modem_info("+CIMI: 262011234567890\r\n\r\nOK\r\n\
+            +COPS: 0,2,\"22003\",7\r\n\r\nOK\r\n".to_string());
+        assert_eq!(info.operator.as_deref(), Some("mt:s"));
+        assert_eq!(info.roaming, Roaming::International);
+        assert!(info.display_signal_info().contains("Network:              220 03 mt:s, Serbia"));
+        assert!(info.display_signal_info().contains("international (home network 262 01 Telekom, Germany)"));
+    }
+}
+
+#[cfg(test)]
+mod parse_report_tests {
+    use crate::modem_tools::modem::get_modem_info;
+    use crate::modem_tools::parse_report::{ParseIssue, ParseProblem};
+
+    // +CSQ answered with nothing, +XACT with an error, +COPS cut short, +XLEC left out
+    static PARTIAL_POLL: &str = "AT+CGMI?\r\n+CGMI: \"Fibocom\"\r\n\r\nOK\r\n\
+        AT+CSQ?\r\n\r\nOK\r\n\
+        AT+XACT?\r\n+CME ERROR: 10\r\n\
+        AT+COPS?\r\n+COPS: 0,0\r\n\r\nOK\r\n\
+        AT+XCCINFO?; +XLEC?; +XMCI=1\r\n\
+        +XMCI: 4,220,03,\"0x4E91\",\"0x00009C03\",\"0x0062\",\"0x000005DC\",\"0x00004C2C\",\"0xFFFFFFFF\",49,17,-4,\"0x00000003\",\"0x00000000\"\r\n\r\nOK\r\n";
+
+    #[test]
+    fn test_partial_poll() {
+        let info = get_modem_info(PARTIAL_POLL.to_string());
+        assert_eq!(info.manufacturer.as_deref(), Some("Fibocom"));
+        assert_eq!((info.csq, info.operator.as_deref(), info.band.as_deref()), (None, None, Some("B3@UnknownMHz")));
+        assert_eq!(info.rsrp, Some(-92));
+        assert_eq!(info.carriers.len(), 1);
+
+        let report = &info.parse_report;
+        assert!(report.issues.contains(&ParseIssue {
+            command: "AT+XACT?".to_string(),
+            problem: ParseProblem::Error { result: "+CME ERROR: 10 (SIM not inserted)".to_string() },
+        }));
+        assert!(report.issues.contains(&ParseIssue { command: "+CSQ".to_string(), problem: ParseProblem::Missing }));
+        assert!(report.issues.contains(&ParseIssue {
+            command: "+COPS".to_string(),
+            problem: ParseProblem::Malformed { line: "+COPS: 0,0".to_string() },
+        }));
+        assert_eq!(report.issues_for(&["+XLEC"]).map(|issue| issue.to_string()).collect::<Vec<_>>(), ["+XLEC missing"]);
+        // Failed commands are not reported twice, commands not polled not at all
+        assert_eq!(report.issues.len(), 4);
+
+        let signal = info.display_signal_info();
+        assert!(signal.contains("Signal:               --%     [░░░░░]"));
+        assert!(signal.contains("RSRP:                 -92dBm"));
+    }
+
+    #[test]
+    fn test_xact_without_lte_bands() {
+        let info = get_modem_info("AT+XACT?\r\n+XACT: 4,1,1,2,4,5,8,\r\n\r\nOK\r\n".to_string());
+        assert_eq!(info.enabled_umts_bands, [1, 2, 4, 5, 8]);
+        assert!(info.enabled_lte_bands.is_empty());
+        assert!(info.parse_report.is_ok());
+    }
+
+    #[test]
+    fn test_empty_poll() {
+        let info = get_modem_info(String::new());
+        assert!(info.parse_report.is_ok());
+        assert!(info.display_signal_info().contains("Operator:             -- (--)"));
+    }
+
+    #[test]
+    fn test_monitor_tab_flags_section() {
+        use ratatui::buffer::Buffer;
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Widget;
+        use crate::display_tools::tui::app_tabs::AppTabs;
+
+        let tabs = AppTabs::default();
+        *tabs.modem_info.lock().unwrap() = get_modem_info(PARTIAL_POLL.to_string());
+        let area = Rect::new(0, 0, 200, 50);
+        let mut buf = Buffer::empty(area);
+        tabs.render(area, &mut buf);
+
+        let screen: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Signal Info [AT+XACT? +CME ERROR: 10 (SIM not inserted), +COPS malformed, +CSQ missing, +XLEC missing]"));
+        assert!(screen.contains("Carrier Info─"));
+    }
+}
+
+#[cfg(test)]
+mod parsers_tests {
+    use crate::modem_tools::modem::get_modem_info;
+    use crate::modem_tools::parsers::cesq::CesqParser;
+    use crate::modem_tools::parsers::csq::CsqParser;
+    use crate::modem_tools::parsers::xlec::XlecParser;
+    use crate::modem_tools::parsers::xmci::XmciParser;
+    use crate::modem_tools::parsers::{MalformedLine, ResponseParser, Responses, PARSERS};
+    use crate::modem_tools::types::ModemInfo;
+
+    static SERVING_CELL: &str = "+XMCI: 4,220,03,\"0x4E91\",\"0x00009C03\",\"0x0062\",\"0x000005DC\",\"0x00004C2C\",\"0xFFFFFFFF\",49,17,-4,\"0x00000003\",\"0x00000000\"";
+    static NEIGHBOUR_CELL: &str = "+XMCI: 5,000,000,\"0xFFFE\",\"0xFFFFFFFF\",\"0x0061\",\"0x00000C1C\",\"0xFFFFFFFF\",\"0xFFFFFFFF\",42,2,255,\"0x7FFFFFFF\",\"0x00000000\"";
+
+    #[test]
+    fn test_responses_grouping() {
+        let responses = Responses::new("AT+CGMI\r\nQuectel\r\n\r\nOK\r\n\
+            AT+GTCCINFO?\r\n+GTCCINFO: \r\nLTE service cell:\r\n1,4,262,02,B17C,01A2D50F,1300,D2,103,5,60,50,58,30\r\n\r\nOK\r\n\
+            AT+XCCINFO?; +XLEC?; +XMCI=1\r\n+XLEC: 0,2,5,3,BAND_LTE_3\r\n+XMCI: 4,...\r\n+XMCI: 5,...\r\n\r\nOK\r\n");
+        // A response without a prefix belongs to the command echoed before it
+        assert_eq!(responses.get("+CGMI"), Some(&["Quectel"][..]));
+        assert_eq!(responses.get("+GTCCINFO").unwrap().len(), 3);
+        assert_eq!(responses.get("+XLEC"), Some(&["+XLEC: 0,2,5,3,BAND_LTE_3"][..]));
+        assert_eq!(responses.get("+XMCI"), Some(&["+XMCI: 4,...", "+XMCI: 5,..."][..]));
+    }
+
+    #[test]
+    fn test_parser_prefixes_unique() {
+        for (i, (prefix, _)) in PARSERS.iter().enumerate() {
+            assert!(prefix.starts_with('+'));
+            assert!(PARSERS[i + 1..].iter().all(|(other, _)| other != prefix), "{} registered twice", prefix);
+        }
+    }
+
+    #[test]
+    fn test_csq_parser() {
+        let mut info = ModemInfo::default();
+        CsqParser.parse(&["+CSQ: 99,99"], &mut info).unwrap();
+        assert_eq!((info.csq, info.csq_perc), (Some(99), None));
+        assert_eq!(CsqParser.parse(&["+CSQ: ,"], &mut info), Err(MalformedLine("+CSQ: ,".to_string())));
+    }
+
+    #[test]
+    fn test_xmci_parser() {
+        let mut info = ModemInfo::default();
+        // UMTS cells (type 2) are not read
+        let umts = "+XMCI: 2,220,03,\"0x4E91\",\"0x0000ABCD\",\"0x0062\",\"0x2A26\"";
+        XmciParser.parse(&[SERVING_CELL, umts, NEIGHBOUR_CELL], &mut info).unwrap();
+        assert_eq!(info.carriers.iter().map(|carrier| carrier.pci).collect::<Vec<_>>(), [98, 97]);
+        assert_eq!((info.rsrp, info.dluarfnc), (Some(-92), Some(1500)));
+        assert_eq!(info.network.unwrap().to_string(), "220 03 mt:s, Serbia");
+
+        let truncated = "+XMCI: 4,220,03,\"0x4E91\"";
+        assert_eq!(XmciParser.parse(&[truncated], &mut ModemInfo::default()), Err(MalformedLine(truncated.to_string())));
+    }
+
+    #[test]
+    fn test_xlec_parser() {
+        let mut info = ModemInfo::default();
+        XmciParser.parse(&[SERVING_CELL, NEIGHBOUR_CELL], &mut info).unwrap();
+        // Two component carriers, the band of the secondary one left out: reported, but
+        // the bands are still taken from the carriers
+        let line = "+XLEC: 0,2,5,3,BAND_LTE_3";
+        assert_eq!(XlecParser.parse(&[line], &mut info), Err(MalformedLine(line.to_string())));
+        assert_eq!(info.band.as_deref(), Some("B3@20MHz B7@10MHz "));
+        assert_eq!(info.carriers.iter().map(|carrier| carrier.bandwidth).collect::<Vec<_>>(), [Some(20.0), Some(10.0)]);
+        assert_eq!(info.rssi, Some(-61));
+
+        let mut info = ModemInfo::default();
+        XmciParser.parse(&[SERVING_CELL, NEIGHBOUR_CELL], &mut info).unwrap();
+        XlecParser.parse(&["+XLEC: 0,2,5,3,BAND_LTE_3,7"], &mut info).unwrap();
+        assert_eq!(info.band.as_deref(), Some("B3@20MHz B7@10MHz "));
+    }
+
+    #[test]
+    fn test_cesq_parser() {
+        let mut info = ModemInfo::default();
+        CesqParser.parse(&["+CESQ: 99,99,35,30,255,255"], &mut info).unwrap();
+        assert_eq!((info.rscp, info.ecio), (Some(-86), Some(-9)));
+        assert_eq!((info.rxlev, info.ber, info.rsrp, info.rsrq), (None, None, None, None));
+
+        CesqParser.parse(&["+CESQ: 40,0,255,255,255,255"], &mut info).unwrap();
+        assert_eq!((info.rxlev, info.ber, info.rscp), (Some(-71), Some(0), None));
+    }
+
+    #[test]
+    fn test_umts_signal_info() {
+        let info = get_modem_info("AT+COPS?\r\n+COPS: 0,0,\"Telekom.de\",6\r\n\r\nOK\r\n\
+            AT+CSQ?\r\n+CSQ: 18,99\r\n\r\nOK\r\n\
+            AT+CESQ\r\n+CESQ: 99,99,35,30,255,255\r\n\r\nOK\r\n".to_string());
+        let signal = info.display_signal_info();
+        assert!(signal.contains("RSCP:                 -86dBm  [██░░░]"));
+        assert!(signal.contains("Ec/Io:                -9dB    [███░░]"));
+        assert!(signal.contains("UARFCN:"));
+        // No LTE rows full of placeholders on 3G
+        assert!(!signal.contains("RSRP"));
+
+        let info = get_modem_info("AT+CESQ\r\n+CESQ: 99,99,255,255,22,47\r\n\r\nOK\r\n".to_string());
+        assert!(info.display_signal_info().contains("RSRP:                 -94dBm"));
+        assert!(!info.display_signal_info().contains("RSCP"));
+    }
+
+    #[test]
+    fn test_gsm_signal_info() {
+        // <AcT> 3 is GSM w/EGPRS, not LTE
+        let info = get_modem_info("AT+COPS?\r\n+COPS: 0,0,\"x\",3\r\n\r\nOK\r\n\
+            AT+CESQ\r\n+CESQ: 40,0,255,255,255,255\r\n\r\nOK\r\n".to_string());
+        assert_eq!(info.mode.as_deref(), Some("EDGE"));
+        let signal = info.display_signal_info();
+        assert!(signal.contains("RXLEV:                -71dBm"));
+        assert!(!signal.contains("RSRP"));
+        assert!(!signal.contains("RSCP"));
+    }
+
+    #[test]
+    fn test_xmci_gsm_timing_advance() {
+        let gsm = "+XMCI: 0,220,03,\"0x4E91\",\"0x00001A2B\",\"0x23\",\"0x0041\",\"0x0041\",\"0x0000\",40,0,\"0x00000002\"";
+        let info = get_modem_info(format!("AT+COPS?\r\n+COPS: 0,0,\"x\",0\r\n\r\nOK\r\n\
+            AT+XMCI=1\r\n{}\r\n\r\nOK\r\n", gsm));
+        assert_eq!(info.gsm_timing_advance, Some(2));
+        assert!(info.parse_report.issues.is_empty());
+        assert!(info.display_signal_info().contains("Distance:             1107m (830-1384m)"));
+
+        let truncated = "+XMCI: 0,220,03,\"0x4E91\"";
+        assert_eq!(XmciParser.parse(&[truncated], &mut ModemInfo::default()), Err(MalformedLine(truncated.to_string())));
+    }
+}
+
+#[cfg(test)]
+mod registration_tests {
+    use crate::modem_tools::modem::{get_modem_info, poll_modem};
+    use crate::modem_tools::registration::{NetworkRegistration, Registration, RegistrationDomain, RegistrationStatus, RejectCause};
+    use crate::modem_tools::supported_modems::detect_modem;
+    use crate::modem_tools::urc::Urc;
+    use crate::tests::test_support::simulated_session;
+
+    #[test]
+    fn test_read_responses() {
+        let eps = Registration::from_response(RegistrationDomain::Eps, "+CEREG: 2,5,\"4E91\",\"00009C03\",7").unwrap();
+        assert_eq!(eps, Registration {
+            status: RegistrationStatus::Roaming,
+            area_code: Some(0x4E91),
+            cell_id: Some(0x9C03),
+            act: Some(7),
+            reject_cause: None,
+        });
+        assert_eq!(eps.describe(RegistrationDomain::Eps), "registered, roaming (EPS, TAC 4E91, CI 9C03)");
+
+        // <n> 0: status only
+        let cs = Registration::from_response(RegistrationDomain::Cs, "+CREG: 0,2").unwrap();
+        assert_eq!((cs.status, cs.area_code), (RegistrationStatus::Searching, None));
+        assert!(Registration::from_response(RegistrationDomain::Cs, "+CREG: 0").is_none());
+    }
+
+    #[test]
+    fn test_reject_causes() {
+        // +CGREG has <rac> before the cause, +C5GREG the allowed NSSAI
+        let ps = Registration::from_response(RegistrationDomain::Ps, "+CGREG: 3,3,\"1A2B\",\"0000ABCD\",2,\"FF\",0,14").unwrap();
+        assert_eq!(ps.reject_cause, Some(RejectCause { vendor_specific: false, cause: 14 }));
+        assert_eq!(ps.describe(RegistrationDomain::Ps), "registration denied (PS, LAC 1A2B, CI ABCD, cause 14 (packet services not allowed in this PLMN))");
+
+        let fivegs = Registration::from_response(RegistrationDomain::Fivegs, "+C5GREG: 3,3,\"00A1B2\",\"0012345678A\",11,,,1,7").unwrap();
+        assert_eq!(fivegs.reject_cause, Some(RejectCause { vendor_specific: true, cause: 7 }));
+        assert_eq!(fivegs.reject_cause.unwrap().to_string(), "vendor cause 7");
+
+        let eps = Registration::from_response(RegistrationDomain::Eps, "+CEREG: 3,3,\"4E91\",\"00009C03\",7,0,11").unwrap();
+        assert_eq!(eps.reject_cause.unwrap().to_string(), "cause 11 (PLMN not allowed)");
+    }
+
+    #[test]
+    fn test_urc_updates() {
+        let mut registration = NetworkRegistration::default();
+        assert!(registration.update(&Urc::unsolicited("+CEREG: 2,\"4E91\",\"00009C03\",7")));
+        assert_eq!(registration.eps.as_ref().unwrap().status, RegistrationStatus::Searching);
+        assert!(registration.update(&Urc::unsolicited("+C5GREG: 0")));
+        assert!(!registration.update(&Urc::unsolicited("+CMTI: \"SM\",3")));
+
+        // The domain in use wins over a more capable one that is not registered
+        let (domain, current) = registration.current().unwrap();
+        assert_eq!((domain, current.status), (RegistrationDomain::Eps, RegistrationStatus::Searching));
+        assert!(registration.update(&Urc::unsolicited("+C5GREG: 1,\"00A1B2\",\"0012345678A\",11")));
+        assert_eq!(registration.current().unwrap().0, RegistrationDomain::Fivegs);
+    }
+
+    #[test]
+    fn test_poll() {
+        let info = get_modem_info("AT+CREG?\r\n+CREG: 2,1,\"4E91\",\"00009C03\",7\r\n\r\nOK\r\n\
+            AT+CEREG?\r\n+CEREG: 2,1,\"4E91\",\"00009C03\",7\r\n\r\nOK\r\n\
+            AT+C5GREG?\r\n+C5GREG: 2\r\n\r\nOK\r\n".to_string());
+        assert!(info.registration.cs.is_some() && info.registration.ps.is_none());
+        assert!(info.display_signal_info().starts_with("Registration:         registered, home network (EPS, TAC 4E91, CI 9C03)\n"));
+        assert_eq!(info.parse_report.issues_for(&["+C5GREG"]).map(|issue| issue.to_string()).collect::<Vec<_>>(), ["+C5GREG malformed"]);
+    }
+
+    #[test]
+    fn test_simulated_denial() {
+        let session = simulated_session("set reg 3\nset cause 11");
+        // Connecting turns on the reject causes
+        let profile = detect_modem(&session).unwrap().profile;
+        let info = poll_modem(&session, profile).parse(profile);
+        let ps = info.registration.ps.as_ref().unwrap();
+        assert_eq!((ps.status, ps.reject_cause.map(|cause| cause.cause)), (RegistrationStatus::Denied, Some(11)));
+        assert!(info.display_signal_info().contains("registration denied (EPS, TAC 4E91, CI 9C03, cause 11 (PLMN not allowed))"));
+        assert!(info.parse_report.is_ok());
+    }
+
+    #[test]
+    fn test_registration_reports_timeout() {
+        let session = simulated_session("timeout AT+CEREG=3");
+        let profile = detect_modem(&session).unwrap().profile;
+        // Fell back to =2: location without reject cause
+        let info = poll_modem(&session, profile).parse(profile);
+        assert_eq!(info.registration.eps.as_ref().and_then(|eps| eps.area_code), Some(0x4E91));
+    }
+}